@@ -1,16 +1,21 @@
+use crate::error::{Error, Result};
 use crate::types::{Collector, CollectorStream};
 use alloy::{
     network::AnyNetwork,
     primitives::{BlockHash, BlockNumber},
     providers::{DynProvider, Provider},
 };
-use anyhow::Result;
 use async_trait::async_trait;
 use futures::StreamExt;
 use std::sync::Arc;
 
 /// A collector that listens for new blocks, and generates a stream of
 /// [events](NewBlock) which contain the block number and hash.
+///
+/// Blocks are forwarded as they arrive, including blocks replacing previous
+/// ones after a reorg. Use a
+/// [ReorgCollector](crate::collectors::reorg_collector::ReorgCollector) to be
+/// told which blocks were orphaned.
 pub struct BlockCollector {
     provider: Arc<DynProvider<AnyNetwork>>,
 }
@@ -34,7 +39,14 @@ impl BlockCollector {
 #[async_trait]
 impl Collector<NewBlock> for BlockCollector {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, NewBlock>> {
-        let subscription = self.provider.subscribe_blocks().await?;
+        let subscription =
+            self.provider
+                .subscribe_blocks()
+                .await
+                .map_err(|source| Error::Subscription {
+                    subscription: "blocks",
+                    source,
+                })?;
         let stream = subscription.into_stream().map(|header| NewBlock {
             hash: header.hash,
             number: header.inner.number,
@@ -0,0 +1,156 @@
+use crate::collectors::block_collector::NewBlock;
+use crate::error::{Error, Result};
+use crate::types::{Collector, CollectorStream};
+use alloy::{
+    network::{AnyNetwork, AnyRpcBlock, AnyRpcHeader, AnyTransactionReceipt},
+    providers::{DynProvider, Provider},
+    rpc::types::BlockTransactionsKind,
+};
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use tracing::error;
+
+/// How many blocks may have their details fetched at the same time.
+const MAX_CONCURRENT_FETCHES: usize = 4;
+
+/// A collector that listens for new blocks, and generates a stream of
+/// [events](BlockDetails) which contain the full block header, and optionally
+/// the block's transactions and receipts.
+///
+/// The header comes with the subscription, so by default no additional
+/// request is made. Transactions and receipts are fetched concurrently for
+/// each block, while later blocks keep being received, and blocks are emitted
+/// in the order they arrived. A block whose details cannot be fetched is still
+/// emitted, with only its header, so that no block is missing from the stream.
+pub struct BlockDetailsCollector {
+    provider: Arc<DynProvider<AnyNetwork>>,
+    transactions: bool,
+    receipts: bool,
+}
+
+/// A new block event, containing the block header and, if requested, the
+/// block with its transactions and the receipts of its transactions.
+#[derive(Debug, Clone)]
+pub struct BlockDetails {
+    /// The block header, with its base fee, timestamp, gas limit and usage,
+    /// parent hash and blob gas fields.
+    pub header: AnyRpcHeader,
+
+    /// The block with its full transactions, if requested.
+    pub block: Option<AnyRpcBlock>,
+
+    /// The receipts of the block's transactions, if requested.
+    pub receipts: Option<Vec<AnyTransactionReceipt>>,
+}
+
+impl BlockDetails {
+    /// Returns the block number and hash.
+    pub fn new_block(&self) -> NewBlock {
+        NewBlock {
+            hash: self.header.hash,
+            number: self.header.inner.number,
+        }
+    }
+}
+
+impl BlockDetailsCollector {
+    pub fn new(provider: Arc<DynProvider<AnyNetwork>>) -> Self {
+        Self {
+            provider,
+            transactions: false,
+            receipts: false,
+        }
+    }
+
+    /// Fetches every block with its full transactions.
+    pub fn with_transactions(mut self) -> Self {
+        self.transactions = true;
+        self
+    }
+
+    /// Fetches the receipts of every block's transactions.
+    pub fn with_receipts(mut self) -> Self {
+        self.receipts = true;
+        self
+    }
+
+    /// Fetches the requested details of the block with the given header.
+    pub async fn fetch_details(&self, header: AnyRpcHeader) -> Result<BlockDetails> {
+        let block = async {
+            if !self.transactions {
+                return Ok(None);
+            }
+            let block = self
+                .provider
+                .get_block_by_hash(header.hash, BlockTransactionsKind::Full)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("block {} not found", header.hash))?;
+            Ok::<_, Error>(Some(block))
+        };
+        let receipts = async {
+            if !self.receipts {
+                return Ok(None);
+            }
+            let receipts = self
+                .provider
+                .get_block_receipts(header.hash.into())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("receipts of block {} not found", header.hash))?;
+            Ok::<_, Error>(Some(receipts))
+        };
+        let (block, receipts) = tokio::try_join!(block, receipts)?;
+        Ok(BlockDetails {
+            header,
+            block,
+            receipts,
+        })
+    }
+
+    /// Turns a stream of block headers into a stream of their details, in the
+    /// same order. Blocks whose details cannot be fetched are emitted with
+    /// only their header, as if no details had been requested.
+    pub fn details_stream<'a, S>(
+        &'a self,
+        headers: S,
+    ) -> impl Stream<Item = BlockDetails> + Send + 'a
+    where
+        S: Stream<Item = AnyRpcHeader> + Send + 'a,
+    {
+        headers
+            .map(move |header| async move {
+                match self.fetch_details(header.clone()).await {
+                    Ok(details) => details,
+                    Err(e) => {
+                        error!(
+                            "error fetching details of block {}, emitting its header only: {}",
+                            header.inner.number, e
+                        );
+                        BlockDetails {
+                            header,
+                            block: None,
+                            receipts: None,
+                        }
+                    }
+                }
+            })
+            .buffered(MAX_CONCURRENT_FETCHES)
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [BlockDetailsCollector](BlockDetailsCollector).
+#[async_trait]
+impl Collector<BlockDetails> for BlockDetailsCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, BlockDetails>> {
+        let subscription =
+            self.provider
+                .subscribe_blocks()
+                .await
+                .map_err(|source| Error::Subscription {
+                    subscription: "blocks",
+                    source,
+                })?;
+        Ok(Box::pin(self.details_stream(subscription.into_stream())))
+    }
+}
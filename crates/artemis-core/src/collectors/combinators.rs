@@ -0,0 +1,262 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    hash::Hash,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::stream;
+use tokio::time::Instant;
+use tokio_stream::StreamExt;
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+
+/// FilterCollector is a wrapper around a [Collector](Collector) that only emits
+/// the events matching a predicate.
+pub struct FilterCollector<E, F> {
+    collector: Box<dyn Collector<E>>,
+    f: F,
+}
+
+impl<E, F> FilterCollector<E, F> {
+    pub fn new(collector: Box<dyn Collector<E>>, f: F) -> Self {
+        Self { collector, f }
+    }
+}
+
+#[async_trait]
+impl<E, F> Collector<E> for FilterCollector<E, F>
+where
+    E: Send + Sync + 'static,
+    F: Fn(&E) -> bool + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?;
+        Ok(Box::pin(stream.filter(|event| (self.f)(event))))
+    }
+}
+
+/// FilterMapCollector is a wrapper around a [Collector](Collector) that maps
+/// events to a different type, dropping those mapped to `None`.
+pub struct FilterMapCollector<E, F> {
+    collector: Box<dyn Collector<E>>,
+    f: F,
+}
+
+impl<E, F> FilterMapCollector<E, F> {
+    pub fn new(collector: Box<dyn Collector<E>>, f: F) -> Self {
+        Self { collector, f }
+    }
+}
+
+#[async_trait]
+impl<E1, E2, F> Collector<E2> for FilterMapCollector<E1, F>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    F: Fn(E1) -> Option<E2> + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E2>> {
+        let stream = self.collector.get_event_stream().await?;
+        Ok(Box::pin(stream.filter_map(|event| (self.f)(event))))
+    }
+}
+
+/// MergeCollector combines several [collectors](Collector) into one, emitting
+/// their events as they arrive. The stream fails to start if any collector
+/// fails to start, and ends when all of their streams end.
+pub struct MergeCollector<E> {
+    collectors: Vec<Box<dyn Collector<E>>>,
+}
+
+impl<E> MergeCollector<E> {
+    pub fn new(collectors: Vec<Box<dyn Collector<E>>>) -> Self {
+        Self { collectors }
+    }
+}
+
+#[async_trait]
+impl<E> Collector<E> for MergeCollector<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let mut streams = Vec::with_capacity(self.collectors.len());
+        for collector in &self.collectors {
+            streams.push(collector.get_event_stream().await?);
+        }
+        Ok(Box::pin(stream::select_all(streams)))
+    }
+}
+
+/// ThrottleCollector is a wrapper around a [Collector](Collector) that emits
+/// at most one event per interval, dropping the events received in between.
+pub struct ThrottleCollector<E> {
+    collector: Box<dyn Collector<E>>,
+    interval: Duration,
+}
+
+impl<E> ThrottleCollector<E> {
+    pub fn new(collector: Box<dyn Collector<E>>, interval: Duration) -> Self {
+        Self {
+            collector,
+            interval,
+        }
+    }
+}
+
+#[async_trait]
+impl<E> Collector<E> for ThrottleCollector<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?;
+        let mut last_emitted: Option<Instant> = None;
+        let stream = stream.filter(move |_| {
+            let now = Instant::now();
+            if last_emitted.is_some_and(|last| now - last < self.interval) {
+                return false;
+            }
+            last_emitted = Some(now);
+            true
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// DebounceCollector is a wrapper around a [Collector](Collector) that waits
+/// for its events to settle: an event is only emitted once no other event was
+/// received for the quiet period, and the events it replaced are dropped.
+pub struct DebounceCollector<E> {
+    collector: Box<dyn Collector<E>>,
+    quiet_period: Duration,
+}
+
+impl<E> DebounceCollector<E> {
+    pub fn new(collector: Box<dyn Collector<E>>, quiet_period: Duration) -> Self {
+        Self {
+            collector,
+            quiet_period,
+        }
+    }
+}
+
+#[async_trait]
+impl<E> Collector<E> for DebounceCollector<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let inner = self.collector.get_event_stream().await?;
+        let quiet_period = self.quiet_period;
+        let stream = stream::unfold(
+            (Some(inner), None::<(E, Instant)>),
+            move |(mut inner, mut pending)| async move {
+                loop {
+                    let Some(stream) = inner.as_mut() else {
+                        // The inner stream ended, flush the last event.
+                        return pending.map(|(event, _)| (event, (None, None)));
+                    };
+                    let deadline = pending.as_ref().map(|(_, deadline)| *deadline);
+                    tokio::select! {
+                        event = stream.next() => match event {
+                            Some(event) => pending = Some((event, Instant::now() + quiet_period)),
+                            None => inner = None,
+                        },
+                        _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)),
+                            if deadline.is_some() =>
+                        {
+                            let (event, _) = pending.take().unwrap();
+                            return Some((event, (inner, None)));
+                        }
+                    }
+                }
+            },
+        );
+        Ok(Box::pin(stream))
+    }
+}
+
+/// BatchCollector is a wrapper around a [Collector](Collector) that groups
+/// events into batches of up to `max_size` events. A batch is emitted when it
+/// is full, or `max_wait` after its first event, whichever comes first.
+pub struct BatchCollector<E> {
+    collector: Box<dyn Collector<E>>,
+    max_size: usize,
+    max_wait: Duration,
+}
+
+impl<E> BatchCollector<E> {
+    /// Creates a batching collector. A `max_size` of 0 is treated as 1.
+    pub fn new(collector: Box<dyn Collector<E>>, max_size: usize, max_wait: Duration) -> Self {
+        Self {
+            collector,
+            max_size: max_size.max(1),
+            max_wait,
+        }
+    }
+}
+
+#[async_trait]
+impl<E> Collector<Vec<E>> for BatchCollector<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Vec<E>>> {
+        let stream = self.collector.get_event_stream().await?;
+        Ok(Box::pin(
+            stream.chunks_timeout(self.max_size, self.max_wait),
+        ))
+    }
+}
+
+/// DedupCollector is a wrapper around a [Collector](Collector) that drops
+/// events whose key, returned by `f`, was already seen.
+///
+/// Only the keys of the last `capacity` distinct events are remembered, e.g.
+/// to drop a transaction reported by several merged mempool collectors.
+pub struct DedupCollector<E, F> {
+    collector: Box<dyn Collector<E>>,
+    capacity: usize,
+    f: F,
+}
+
+impl<E, F> DedupCollector<E, F> {
+    pub fn new(collector: Box<dyn Collector<E>>, capacity: usize, f: F) -> Self {
+        Self {
+            collector,
+            capacity,
+            f,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, K, F> Collector<E> for DedupCollector<E, F>
+where
+    E: Send + Sync + 'static,
+    K: Hash + Eq + Clone + Send + 'static,
+    F: Fn(&E) -> K + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?;
+        let mut seen = HashSet::new();
+        let mut order = VecDeque::new();
+        let stream = stream.filter(move |event| {
+            let key = (self.f)(event);
+            if !seen.insert(key.clone()) {
+                return false;
+            }
+            order.push_back(key);
+            if order.len() > self.capacity {
+                if let Some(oldest) = order.pop_front() {
+                    seen.remove(&oldest);
+                }
+            }
+            true
+        });
+        Ok(Box::pin(stream))
+    }
+}
@@ -1,10 +1,10 @@
+use crate::error::{Error, Result};
 use crate::types::{Collector, CollectorStream};
 use alloy::{
     network::AnyNetwork,
     providers::{DynProvider, Provider},
     rpc::types::{Filter, Log},
 };
-use anyhow::Result;
 use async_trait::async_trait;
 //use ethers::{
 //    prelude::Middleware,
@@ -32,7 +32,14 @@ impl LogCollector {
 #[async_trait]
 impl Collector<Log> for LogCollector {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, Log>> {
-        let sub = self.provider.subscribe_logs(&self.filter).await?;
+        let sub = self
+            .provider
+            .subscribe_logs(&self.filter)
+            .await
+            .map_err(|source| Error::Subscription {
+                subscription: "logs",
+                source,
+            })?;
         let stream = sub.into_stream();
         Ok(Box::pin(stream))
     }
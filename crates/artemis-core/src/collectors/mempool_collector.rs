@@ -8,8 +8,8 @@ use alloy::{
 use std::sync::Arc;
 use tracing::error;
 
+use crate::error::{Error, Result};
 use crate::types::{Collector, CollectorStream};
-use anyhow::Result;
 use tokio_stream::StreamExt;
 
 /// A collector that listens for new transactions in the mempool, and generates a stream of
@@ -35,10 +35,10 @@ impl Collector<WithOtherFields<Transaction<AnyTxEnvelope>>> for MempoolCollector
             Ok(sub) => sub,
             Err(e) => {
                 error!("Error subscribing to pending transactions: {:?}", e);
-                return Err(anyhow::anyhow!(
-                    "Error subscribing to pending transactions: {:?}",
-                    e
-                ));
+                return Err(Error::Subscription {
+                    subscription: "pending transactions",
+                    source: e,
+                });
             }
         };
         let stream = sub
@@ -5,8 +5,23 @@
 /// This collector listens to a stream of new blocks.
 pub mod block_collector;
 
+/// This collector listens to a stream of new blocks, along with their headers
+/// and optionally their transactions and receipts.
+pub mod block_details_collector;
+
+/// This module contains wrappers filtering, merging, throttling, batching and
+/// deduplicating the events of collectors.
+pub mod combinators;
+
 /// This collector listens to a stream of new event logs.
 pub mod log_collector;
 
 /// This collector listens to a stream of new pending transactions.
 pub mod mempool_collector;
+
+/// This collector listens to a stream of new blocks and detects chain
+/// reorganizations.
+pub mod reorg_collector;
+
+/// This collector emits the outcome of transactions submitted by executors.
+pub mod tx_outcome_collector;
@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+
+use crate::collectors::block_collector::NewBlock;
+use crate::error::{Error, Result};
+use crate::types::{Collector, CollectorStream};
+use alloy::{
+    network::AnyNetwork,
+    primitives::BlockHash,
+    providers::{DynProvider, Provider},
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+use tracing::warn;
+
+/// A collector that listens for new blocks like a
+/// [BlockCollector](crate::collectors::block_collector::BlockCollector), and
+/// also detects chain reorganizations, generating a stream of
+/// [events](BlockEvent).
+///
+/// The collector remembers the last `depth` blocks. When a new block does not
+/// extend the chain they form, the remembered blocks it replaces are reported
+/// as dropped in a [BlockEvent::Reorg]. Reorgs deeper than `depth` blocks are
+/// only partially reported.
+pub struct ReorgCollector {
+    provider: Arc<DynProvider<AnyNetwork>>,
+    depth: usize,
+}
+
+/// A new block, or a new block replacing previously emitted ones.
+#[derive(Debug, Clone)]
+pub enum BlockEvent {
+    /// A block extending the chain.
+    NewBlock(NewBlock),
+
+    /// A block replacing previously emitted blocks, which are no longer part
+    /// of the chain.
+    Reorg {
+        /// The orphaned blocks, oldest first.
+        dropped: Vec<NewBlock>,
+        /// The new block, which is the new head of the chain.
+        new_head: NewBlock,
+    },
+}
+
+/// Tracks the recent blocks of a chain from their headers, detecting when new
+/// blocks replace previous ones.
+///
+/// Nodes report every block of the new chain after a reorg, starting with the
+/// block following the fork point, so a reorg is detected when that block is
+/// pushed: every remembered block at or above its number is dropped, as is its
+/// expected parent if that was replaced too.
+#[derive(Debug, Clone)]
+pub struct ReorgTracker {
+    depth: usize,
+    /// The remembered blocks with their parent hash, oldest first.
+    blocks: VecDeque<(NewBlock, BlockHash)>,
+}
+
+impl ReorgTracker {
+    /// Creates a tracker remembering the last `depth` blocks. A `depth` of 0
+    /// is treated as 1.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth: depth.max(1),
+            blocks: VecDeque::new(),
+        }
+    }
+
+    /// Records a new block, returning the event to emit for it, or `None` if
+    /// the block was already seen.
+    pub fn push(&mut self, block: NewBlock, parent_hash: BlockHash) -> Option<BlockEvent> {
+        if self.blocks.iter().any(|(seen, _)| seen.hash == block.hash) {
+            return None;
+        }
+
+        let mut dropped = vec![];
+        while let Some((tip, _)) = self.blocks.back() {
+            let replaced = tip.number >= block.number
+                || (tip.number + 1 == block.number && tip.hash != parent_hash);
+            if !replaced {
+                break;
+            }
+            let (tip, _) = self.blocks.pop_back().unwrap();
+            dropped.push(tip);
+        }
+        dropped.reverse();
+
+        self.blocks.push_back((block.clone(), parent_hash));
+        while self.blocks.len() > self.depth {
+            self.blocks.pop_front();
+        }
+
+        if dropped.is_empty() {
+            Some(BlockEvent::NewBlock(block))
+        } else {
+            Some(BlockEvent::Reorg {
+                dropped,
+                new_head: block,
+            })
+        }
+    }
+}
+
+impl ReorgCollector {
+    pub fn new(provider: Arc<DynProvider<AnyNetwork>>) -> Self {
+        Self {
+            provider,
+            depth: 64,
+        }
+    }
+
+    /// Sets how many recent blocks are remembered to detect reorgs. Defaults
+    /// to 64.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [ReorgCollector](ReorgCollector).
+#[async_trait]
+impl Collector<BlockEvent> for ReorgCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, BlockEvent>> {
+        let subscription =
+            self.provider
+                .subscribe_blocks()
+                .await
+                .map_err(|source| Error::Subscription {
+                    subscription: "blocks",
+                    source,
+                })?;
+        let mut tracker = ReorgTracker::new(self.depth);
+        let stream = subscription.into_stream().filter_map(move |header| {
+            let block = NewBlock {
+                hash: header.hash,
+                number: header.inner.number,
+            };
+            let event = tracker.push(block, header.inner.parent_hash);
+            if let Some(BlockEvent::Reorg { dropped, new_head }) = &event {
+                warn!(
+                    "chain reorg at block {}, dropped {} blocks",
+                    new_head.number,
+                    dropped.len()
+                );
+            }
+            futures::future::ready(event)
+        });
+        Ok(Box::pin(stream))
+    }
+}
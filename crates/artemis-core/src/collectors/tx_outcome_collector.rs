@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tracing::warn;
+
+use crate::error::Result;
+use crate::executors::tx_tracker::TxOutcome;
+use crate::types::{Collector, CollectorStream};
+
+/// A collector that emits the [outcome](TxOutcome) of the transactions
+/// submitted by executors, as reported by a
+/// [TxTracker](crate::executors::tx_tracker::TxTracker).
+pub struct TxOutcomeCollector {
+    outcomes: broadcast::Sender<TxOutcome>,
+}
+
+impl TxOutcomeCollector {
+    pub(crate) fn new(outcomes: broadcast::Sender<TxOutcome>) -> Self {
+        Self { outcomes }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [TxOutcomeCollector](TxOutcomeCollector). Only outcomes published after the
+/// stream is created are emitted.
+#[async_trait]
+impl Collector<TxOutcome> for TxOutcomeCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, TxOutcome>> {
+        let stream =
+            BroadcastStream::new(self.outcomes.subscribe()).filter_map(|outcome| match outcome {
+                Ok(outcome) => Some(outcome),
+                Err(e) => {
+                    warn!("transaction outcome collector {}", e);
+                    None
+                }
+            });
+        Ok(Box::pin(stream))
+    }
+}
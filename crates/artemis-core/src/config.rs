@@ -0,0 +1,271 @@
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What a component does when it falls behind its broadcast channel and
+/// misses messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Report and count the missed messages, then carry on with the next one.
+    #[default]
+    Drop,
+
+    /// Call [Strategy::sync_state](crate::types::Strategy::sync_state) to rebuild
+    /// state before carrying on. Executors have no state to sync and treat this
+    /// like [LagPolicy::Drop].
+    Resync,
+
+    /// Shut the engine down.
+    FailFast,
+}
+
+/// How a collector is restarted when its event stream fails to start or ends.
+///
+/// Consecutive restarts are delayed with an exponential backoff, which is reset
+/// as soon as the collector emits an event again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Delay before the first restart.
+    pub initial_backoff: Duration,
+
+    /// Upper bound for the delay between two restarts.
+    pub max_backoff: Duration,
+
+    /// Maximum number of consecutive restarts before giving up, or `None` to
+    /// restart forever.
+    pub max_restarts: Option<u32>,
+}
+
+impl RestartPolicy {
+    /// A policy that never restarts the collector.
+    pub fn never() -> Self {
+        Self {
+            max_restarts: Some(0),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay before the restart following `attempt` consecutive
+    /// restarts.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_restarts: None,
+        }
+    }
+}
+
+/// A predicate deciding which events or actions a component receives.
+pub(crate) struct Filter<T>(Box<dyn Fn(&T) -> bool + Send + Sync>);
+
+impl<T> Filter<T> {
+    /// Returns true if the component should receive the item. Components
+    /// without a filter receive everything.
+    pub(crate) fn accepts(filter: &Option<Self>, item: &T) -> bool {
+        filter.as_ref().is_none_or(|filter| (filter.0)(item))
+    }
+}
+
+impl<T> std::fmt::Debug for Filter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Filter")
+    }
+}
+
+/// Extracts the key under which an executor keeps actions in order.
+pub(crate) struct OrderingKey<T>(Box<dyn Fn(&T) -> u64 + Send + Sync>);
+
+impl<T> OrderingKey<T> {
+    /// Returns the hashed ordering key of the item.
+    pub(crate) fn of(&self, item: &T) -> u64 {
+        (self.0)(item)
+    }
+}
+
+impl<T> std::fmt::Debug for OrderingKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("OrderingKey")
+    }
+}
+
+/// A shared counter reported by a running component.
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    /// Returns the current value of the counter.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// Configuration of a collector added to an [Engine](crate::engine::Engine).
+#[derive(Debug)]
+pub struct CollectorConfig {
+    pub(crate) name: String,
+    pub(crate) restart_policy: RestartPolicy,
+    pub(crate) restarts: Counter,
+}
+
+impl CollectorConfig {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            restart_policy: RestartPolicy::default(),
+            restarts: Counter::default(),
+        }
+    }
+
+    /// Sets the name used to identify the collector in logs and reports.
+    pub fn with_name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets how the collector is restarted when its stream fails or ends.
+    pub fn with_restart_policy(&mut self, policy: RestartPolicy) -> &mut Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Returns a counter of how many times the collector was restarted.
+    pub fn restart_counter(&self) -> Counter {
+        self.restarts.clone()
+    }
+}
+
+/// Configuration of a strategy added to an [Engine](crate::engine::Engine).
+#[derive(Debug)]
+pub struct StrategyConfig<E> {
+    pub(crate) name: String,
+    pub(crate) lag_policy: LagPolicy,
+    pub(crate) lagged: Counter,
+    pub(crate) filter: Option<Filter<E>>,
+}
+
+impl<E> StrategyConfig<E> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            lag_policy: LagPolicy::default(),
+            lagged: Counter::default(),
+            filter: None,
+        }
+    }
+
+    /// Sets the name used to identify the strategy in logs and reports.
+    pub fn with_name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets what the strategy does when it misses events.
+    pub fn with_lag_policy(&mut self, policy: LagPolicy) -> &mut Self {
+        self.lag_policy = policy;
+        self
+    }
+
+    /// Only delivers the events matching the predicate to the strategy, e.g.
+    /// `|event| matches!(event, Events::NewBlock(_))`. Other events are skipped
+    /// without being cloned.
+    pub fn with_event_filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Filter(Box::new(filter)));
+        self
+    }
+
+    /// Returns a counter of the events this strategy missed.
+    pub fn lag_counter(&self) -> Counter {
+        self.lagged.clone()
+    }
+}
+
+/// Configuration of an executor added to an [Engine](crate::engine::Engine).
+#[derive(Debug)]
+pub struct ExecutorConfig<A> {
+    pub(crate) name: String,
+    pub(crate) lag_policy: LagPolicy,
+    pub(crate) lagged: Counter,
+    pub(crate) filter: Option<Filter<A>>,
+    pub(crate) concurrency: usize,
+    pub(crate) ordering_key: Option<OrderingKey<A>>,
+}
+
+impl<A> ExecutorConfig<A> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            lag_policy: LagPolicy::default(),
+            lagged: Counter::default(),
+            filter: None,
+            concurrency: 1,
+            ordering_key: None,
+        }
+    }
+
+    /// Sets the name used to identify the executor in logs and reports.
+    pub fn with_name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets what the executor does when it misses actions.
+    pub fn with_lag_policy(&mut self, policy: LagPolicy) -> &mut Self {
+        self.lag_policy = policy;
+        self
+    }
+
+    /// Only routes the actions matching the predicate to the executor. Other
+    /// actions are skipped without being cloned.
+    pub fn with_action_filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&A) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Filter(Box::new(filter)));
+        self
+    }
+
+    /// Sets how many actions the executor may execute at the same time.
+    /// Defaults to 1, which executes actions one after the other. A limit of
+    /// 0 is treated as 1.
+    pub fn with_concurrency(&mut self, limit: usize) -> &mut Self {
+        self.concurrency = limit.max(1);
+        self
+    }
+
+    /// Executes actions sharing the same key, e.g. the sender address, in the
+    /// order they were produced, while actions with different keys may still
+    /// run concurrently.
+    pub fn with_ordering_key<K, F>(&mut self, key: F) -> &mut Self
+    where
+        K: Hash,
+        F: Fn(&A) -> K + Send + Sync + 'static,
+    {
+        let hasher = RandomState::new();
+        self.ordering_key = Some(OrderingKey(Box::new(move |action| {
+            hasher.hash_one(key(action))
+        })));
+        self
+    }
+
+    /// Returns a counter of the actions this executor missed.
+    pub fn lag_counter(&self) -> Counter {
+        self.lagged.clone()
+    }
+}
@@ -1,37 +1,84 @@
-use tokio::sync::broadcast::{self, Sender};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast::{self, error::RecvError, Sender};
+use tokio::sync::oneshot::{self, error::TryRecvError};
+use tokio::sync::{watch, Semaphore};
 use tokio::task::JoinSet;
 use tokio_stream::StreamExt;
-use tracing::{error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
+use crate::config::{CollectorConfig, Counter, ExecutorConfig, Filter, LagPolicy, StrategyConfig};
+use crate::envelope::Envelope;
+use crate::error::{Error, Result};
+#[cfg(feature = "metrics")]
+use crate::metrics::EngineMetrics;
+use crate::metrics::Metrics;
+use crate::shutdown::{ShutdownHandle, ShutdownSummary};
+use crate::status::{ComponentKind, ComponentState, StatusHandle, StatusRegistry};
 use crate::types::{Collector, Executor, Strategy};
 
+/// A collector added to the engine, along with its configuration.
+type CollectorEntry<E> = (Box<dyn Collector<E>>, CollectorConfig);
+
+/// A strategy added to the engine, along with its configuration.
+type StrategyEntry<E, A> = (Box<dyn Strategy<E, A>>, StrategyConfig<E>);
+
+/// An executor added to the engine, along with its configuration.
+type ExecutorEntry<A> = (Box<dyn Executor<A>>, ExecutorConfig<A>);
+
 /// The main engine of Artemis. This struct is responsible for orchestrating the
 /// data flow between collectors, strategies, and executors.
 pub struct Engine<E, A> {
     /// The set of collectors that the engine will use to collect events.
-    collectors: Vec<Box<dyn Collector<E>>>,
+    collectors: Vec<CollectorEntry<E>>,
 
     /// The set of strategies that the engine will use to process events.
-    strategies: Vec<Box<dyn Strategy<E, A>>>,
+    strategies: Vec<StrategyEntry<E, A>>,
 
     /// The set of executors that the engine will use to execute actions.
-    executors: Vec<Box<dyn Executor<A>>>,
+    executors: Vec<ExecutorEntry<A>>,
 
     /// The capacity of the event channel.
     event_channel_capacity: usize,
 
     /// The capacity of the action channel.
     action_channel_capacity: usize,
+
+    /// How long components are given to stop and drain once shutdown is
+    /// requested, before they are aborted.
+    shutdown_timeout: Duration,
+
+    /// Token cancelled when shutdown is requested.
+    shutdown: CancellationToken,
+
+    /// Channel on which the shutdown summary is published once the engine stops.
+    summary: watch::Sender<Option<ShutdownSummary>>,
+
+    /// Live status of the components, registered when the engine runs.
+    status: StatusRegistry,
+
+    /// Metrics recorded by the components.
+    metrics: Metrics,
 }
 
 impl<E, A> Engine<E, A> {
     pub fn new() -> Self {
+        let (summary, _) = watch::channel(None);
         Self {
             collectors: vec![],
             strategies: vec![],
             executors: vec![],
             event_channel_capacity: 512,
             action_channel_capacity: 512,
+            shutdown_timeout: Duration::from_secs(10),
+            shutdown: CancellationToken::new(),
+            summary,
+            status: StatusRegistry::new(),
+            metrics: Metrics::default(),
         }
     }
 
@@ -44,6 +91,30 @@ impl<E, A> Engine<E, A> {
         self.action_channel_capacity = capacity;
         self
     }
+
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Records Prometheus metrics for every component into the given metrics.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: EngineMetrics) -> Self {
+        self.metrics = Metrics::new(metrics);
+        self
+    }
+
+    /// Returns a handle that can be used to gracefully shut down the engine
+    /// once it is running.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle::new(self.shutdown.clone(), self.summary.subscribe())
+    }
+
+    /// Returns a handle that reports the status of each component once the
+    /// engine is running.
+    pub fn status_handle(&self) -> StatusHandle {
+        self.status.handle()
+    }
 }
 
 impl<E, A> Default for Engine<E, A> {
@@ -54,89 +125,446 @@ impl<E, A> Default for Engine<E, A> {
 
 impl<E, A> Engine<E, A>
 where
-    E: Send + Clone + 'static + std::fmt::Debug,
-    A: Send + Clone + 'static + std::fmt::Debug,
+    E: Send + Sync + Clone + 'static + std::fmt::Debug,
+    A: Send + Sync + Clone + 'static + std::fmt::Debug,
 {
-    /// Adds a collector to be used by the engine.
-    pub fn add_collector(&mut self, collector: Box<dyn Collector<E>>) {
-        self.collectors.push(collector);
+    /// Adds a collector to be used by the engine, returning its configuration.
+    pub fn add_collector(&mut self, collector: Box<dyn Collector<E>>) -> &mut CollectorConfig {
+        let config = CollectorConfig::new(format!("collector-{}", self.collectors.len()));
+        self.collectors.push((collector, config));
+        &mut self.collectors.last_mut().unwrap().1
     }
 
-    /// Adds a strategy to be used by the engine.
-    pub fn add_strategy(&mut self, strategy: Box<dyn Strategy<E, A>>) {
-        self.strategies.push(strategy);
+    /// Adds a strategy to be used by the engine, returning its configuration.
+    pub fn add_strategy(&mut self, strategy: Box<dyn Strategy<E, A>>) -> &mut StrategyConfig<E> {
+        let config = StrategyConfig::new(format!("strategy-{}", self.strategies.len()));
+        self.strategies.push((strategy, config));
+        &mut self.strategies.last_mut().unwrap().1
     }
 
-    /// Adds an executor to be used by the engine.
-    pub fn add_executor(&mut self, executor: Box<dyn Executor<A>>) {
-        self.executors.push(executor);
+    /// Adds an executor to be used by the engine, returning its configuration.
+    pub fn add_executor(&mut self, executor: Box<dyn Executor<A>>) -> &mut ExecutorConfig<A> {
+        let config = ExecutorConfig::new(format!("executor-{}", self.executors.len()));
+        self.executors.push((executor, config));
+        &mut self.executors.last_mut().unwrap().1
     }
 
     /// The core run loop of the engine. This function will spawn a thread for
     /// each collector, strategy, and executor. It will then orchestrate the
     /// data flow between them.
-    pub async fn run(self) -> Result<JoinSet<()>, Box<dyn std::error::Error>> {
-        let (event_sender, _): (Sender<E>, _) = broadcast::channel(self.event_channel_capacity);
-        let (action_sender, _): (Sender<A>, _) = broadcast::channel(self.action_channel_capacity);
+    ///
+    /// Events and actions are shared between components and only cloned for the
+    /// strategies and executors whose filters accept them.
+    ///
+    /// Each executor runs up to its configured concurrency limit of actions at
+    /// a time, keeping actions with the same ordering key in order.
+    ///
+    /// Every event is tagged with a correlation ID that is carried over to the
+    /// actions produced from it. Strategies process events and executors execute
+    /// actions inside `process_event` and `execute` tracing spans, which record
+    /// the correlation ID, the source collector and the originating strategy.
+    ///
+    /// The returned set contains a single task which completes once the engine
+    /// has stopped, either because shutdown was requested through a
+    /// [ShutdownHandle] or because all collectors finished on their own.
+    ///
+    /// Fails with [Error::StrategySync] if a strategy cannot sync its initial
+    /// state, or with [Error::Shutdown] if shutdown was requested before the
+    /// engine started.
+    pub async fn run(self) -> Result<JoinSet<()>> {
+        if self.shutdown.is_cancelled() {
+            return Err(Error::Shutdown);
+        }
 
-        let mut set = JoinSet::new();
+        let (event_sender, _): (Sender<Envelope<Arc<E>>>, _) =
+            broadcast::channel(self.event_channel_capacity);
+        let (action_sender, _): (Sender<Envelope<Arc<A>>>, _) =
+            broadcast::channel(self.action_channel_capacity);
+
+        let shutdown = self.shutdown;
+        let events_drained = Arc::new(AtomicU64::new(0));
+        let actions_drained = Arc::new(AtomicU64::new(0));
+
+        let mut collectors = JoinSet::new();
+        let mut strategies = JoinSet::new();
+        let mut executors = JoinSet::new();
 
-        // Spawn executors in separate threads.
-        for executor in self.executors {
+        // Spawn executors in separate threads. Each action is executed in its own
+        // task, bounded by the executor's concurrency limit.
+        let max_queued = self.action_channel_capacity;
+        for (executor, config) in self.executors {
+            let executor: Arc<dyn Executor<A>> = executor.into();
             let mut receiver = action_sender.subscribe();
-            set.spawn(async move {
-                info!("starting executor... ");
+            let shutdown = shutdown.clone();
+            let actions_drained = actions_drained.clone();
+            let stats = self.status.register(
+                &config.name,
+                ComponentKind::Executor,
+                config.lagged.clone(),
+                Counter::default(),
+            );
+            let metrics = self.metrics.executor(&config.name);
+            executors.spawn(async move {
+                let name: Arc<str> = config.name.into();
+                let permits = Arc::new(Semaphore::new(config.concurrency));
+                let mut tasks = JoinSet::new();
+                // Completion of the last action scheduled for each ordering key.
+                let mut pending: HashMap<u64, oneshot::Receiver<()>> = HashMap::new();
+                info!("starting executor {}...", name);
+                stats.set_state(ComponentState::Running);
                 loop {
                     match receiver.recv().await {
-                        Ok(action) => match executor.execute(action).await {
-                            Ok(_) => {}
-                            Err(e) => error!("error executing action: {}", e),
-                        },
-                        Err(e) => error!("error receiving action: {}", e),
+                        Ok(action) if !Filter::accepts(&config.filter, &action.payload) => {}
+                        Ok(action) => {
+                            // Waiting for queued actions to complete applies
+                            // backpressure to the action channel once too many
+                            // are waiting for a permit or their turn.
+                            while tasks.len() >= max_queued.max(config.concurrency) {
+                                tasks.join_next().await;
+                            }
+                            let ordering = config.ordering_key.as_ref().map(|key| {
+                                pending.retain(|_, done| {
+                                    matches!(done.try_recv(), Err(TryRecvError::Empty))
+                                });
+                                let (done, receiver) = oneshot::channel();
+                                let previous = pending.insert(key.of(&action.payload), receiver);
+                                (previous, done)
+                            });
+                            // Actions free to run take their permit here, in the
+                            // order they were received. Actions waiting for an
+                            // earlier action with the same key take it once
+                            // their turn came, so they do not hold permits other
+                            // keys could use.
+                            let permit = match &ordering {
+                                Some((Some(_), _)) => None,
+                                _ => Some(permits.clone().acquire_owned().await.unwrap()),
+                            };
+                            let span = info_span!(
+                                "execute",
+                                correlation_id = action.correlation_id,
+                                source = %action.source,
+                                strategy = %action.strategy.as_deref().unwrap_or_default(),
+                                executor = %name,
+                            );
+                            let executor = executor.clone();
+                            let permits = permits.clone();
+                            let shutdown = shutdown.clone();
+                            let actions_drained = actions_drained.clone();
+                            let stats = stats.clone();
+                            let metrics = metrics.clone();
+                            tasks.spawn(
+                                async move {
+                                    // Dropped once the action has been executed,
+                                    // releasing the next action with the same key.
+                                    let _done = match ordering {
+                                        Some((previous, done)) => {
+                                            if let Some(previous) = previous {
+                                                let _ = previous.await;
+                                            }
+                                            Some(done)
+                                        }
+                                        None => None,
+                                    };
+                                    let permit = match permit {
+                                        Some(permit) => permit,
+                                        None => permits.acquire_owned().await.unwrap(),
+                                    };
+                                    let started = Instant::now();
+                                    let result =
+                                        executor.execute(action.payload.as_ref().clone()).await;
+                                    metrics.action_executed(
+                                        started,
+                                        action.received_at,
+                                        result.is_ok(),
+                                    );
+                                    let since_produced = action
+                                        .produced_at
+                                        .map(|at| at.elapsed())
+                                        .unwrap_or_default();
+                                    match result {
+                                        Ok(_) => debug!(
+                                            "executed action, latency {:?} ({:?} since produced)",
+                                            action.received_at.elapsed(),
+                                            since_produced,
+                                        ),
+                                        Err(e) => {
+                                            error!("error executing action: {}", e);
+                                            stats.record_error();
+                                        }
+                                    }
+                                    stats.record_processed();
+                                    if shutdown.is_cancelled() {
+                                        actions_drained.fetch_add(1, Ordering::Relaxed);
+                                    }
+                                    drop(permit);
+                                }
+                                .instrument(span),
+                            );
+                            while tasks.try_join_next().is_some() {}
+                        }
+                        Err(RecvError::Closed) => break,
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!(
+                                "executor {} lagged behind and missed {} actions",
+                                name, missed
+                            );
+                            config.lagged.add(missed);
+                            if config.lag_policy == LagPolicy::FailFast {
+                                error!("executor {} cannot tolerate lag, shutting down", name);
+                                stats.set_state(ComponentState::Failed);
+                                shutdown.cancel();
+                                break;
+                            }
+                        }
                     }
                 }
+                join_all(&mut tasks).await;
+                stats.stop();
+                info!("executor {} stopped", name);
             });
         }
 
         // Spawn strategies in separate threads.
-        for mut strategy in self.strategies {
+        for (mut strategy, config) in self.strategies {
             let mut event_receiver = event_sender.subscribe();
             let action_sender = action_sender.clone();
-            strategy.sync_state().await?;
+            let shutdown = shutdown.clone();
+            let events_drained = events_drained.clone();
+            let stats = self.status.register(
+                &config.name,
+                ComponentKind::Strategy,
+                config.lagged.clone(),
+                Counter::default(),
+            );
+            let metrics = self.metrics.strategy(&config.name);
+            if let Err(e) = strategy.sync_state().await {
+                stats.set_state(ComponentState::Failed);
+                return Err(Error::StrategySync {
+                    strategy: config.name,
+                    source: Box::new(e),
+                });
+            }
 
-            set.spawn(async move {
-                info!("starting strategy... ");
+            strategies.spawn(async move {
+                let name: Arc<str> = config.name.into();
+                info!("starting strategy {}...", name);
+                stats.set_state(ComponentState::Running);
                 loop {
                     match event_receiver.recv().await {
+                        Ok(event) if !Filter::accepts(&config.filter, &event.payload) => {}
                         Ok(event) => {
-                            for action in strategy.process_event(event).await {
+                            let (event, payload) = event.split();
+                            let span = info_span!(
+                                "process_event",
+                                correlation_id = event.correlation_id,
+                                source = %event.source,
+                                strategy = %name,
+                            );
+                            let started = Instant::now();
+                            let actions = strategy
+                                .process_event(payload.as_ref().clone())
+                                .instrument(span)
+                                .await;
+                            let emitted = actions.len();
+                            for action in actions {
+                                let action = event.action(name.clone(), Arc::new(action));
                                 match action_sender.send(action) {
                                     Ok(_) => {}
                                     Err(e) => error!("error sending action: {}", e),
                                 }
                             }
+                            metrics.event_processed(started, emitted, action_sender.len());
+                            stats.record_processed();
+                            if shutdown.is_cancelled() {
+                                events_drained.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                        Err(RecvError::Closed) => break,
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!(
+                                "strategy {} lagged behind and missed {} events",
+                                name, missed
+                            );
+                            config.lagged.add(missed);
+                            strategy.on_lag(missed).await;
+                            match config.lag_policy {
+                                LagPolicy::Drop => {}
+                                LagPolicy::Resync => {
+                                    if let Err(e) = strategy.sync_state().await {
+                                        error!("error resyncing strategy {}: {}", name, e);
+                                        stats.record_error();
+                                    }
+                                }
+                                LagPolicy::FailFast => {
+                                    error!("strategy {} cannot tolerate lag, shutting down", name);
+                                    stats.set_state(ComponentState::Failed);
+                                    shutdown.cancel();
+                                    break;
+                                }
+                            }
                         }
-                        Err(e) => error!("error receiving event: {}", e),
                     }
                 }
+                stats.stop();
+                info!("strategy {} stopped", name);
             });
         }
 
-        // Spawn collectors in separate threads.
-        for collector in self.collectors {
+        // Spawn collectors in separate threads. Each collector is supervised and
+        // restarted according to its restart policy when its stream fails or ends.
+        for (collector, config) in self.collectors {
             let event_sender = event_sender.clone();
-            set.spawn(async move {
-                info!("starting collector... ");
-                let mut event_stream = collector.get_event_stream().await.unwrap();
-                while let Some(event) = event_stream.next().await {
-                    match event_sender.send(event) {
-                        Ok(_) => {}
-                        Err(e) => error!("error sending event: {}", e),
+            let shutdown = shutdown.clone();
+            let stats = self.status.register(
+                &config.name,
+                ComponentKind::Collector,
+                Counter::default(),
+                config.restarts.clone(),
+            );
+            let metrics = self.metrics.collector(&config.name);
+            collectors.spawn(async move {
+                let name: Arc<str> = config.name.into();
+                let policy = config.restart_policy;
+                let mut attempt = 0;
+                loop {
+                    info!("starting collector {}...", name);
+                    // Starting the stream may hang, e.g. on a stalled handshake,
+                    // so it must not hold up shutdown.
+                    let stream = tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        stream = collector.get_event_stream() => stream,
+                    };
+                    match stream {
+                        Ok(mut event_stream) => {
+                            stats.set_state(ComponentState::Running);
+                            loop {
+                                tokio::select! {
+                                    _ = shutdown.cancelled() => break,
+                                    event = event_stream.next() => match event {
+                                        Some(event) => {
+                                            attempt = 0;
+                                            stats.record_processed();
+                                            let event =
+                                                Envelope::event(name.clone(), Arc::new(event));
+                                            if let Err(e) = event_sender.send(event) {
+                                                error!("error sending event: {}", e);
+                                            }
+                                            metrics.event_emitted(event_sender.len());
+                                        }
+                                        None => {
+                                            warn!("collector {} stream ended", name);
+                                            break;
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            error!("error starting collector {}: {}", name, e);
+                            stats.record_error();
+                        }
+                    }
+
+                    if shutdown.is_cancelled() {
+                        break;
+                    }
+                    if policy.max_restarts.is_some_and(|max| attempt >= max) {
+                        error!("collector {} gave up after {} restarts", name, attempt);
+                        stats.set_state(ComponentState::Failed);
+                        break;
+                    }
+                    let backoff = policy.backoff(attempt);
+                    attempt += 1;
+                    config.restarts.add(1);
+                    warn!("restarting collector {} in {:?}", name, backoff);
+                    stats.set_state(ComponentState::Restarting);
+                    tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = tokio::time::sleep(backoff) => {}
                     }
                 }
+                stats.stop();
+                info!("collector {} stopped", name);
             });
         }
 
+        // From here on only the components hold the senders, so the channels
+        // close as soon as the upstream stage has stopped.
+        drop(event_sender);
+        drop(action_sender);
+
+        let shutdown_timeout = self.shutdown_timeout;
+        let summary = self.summary;
+        let status = self.status;
+        let mut set = JoinSet::new();
+        set.spawn(async move {
+            let total_collectors = collectors.len();
+            let total_strategies = strategies.len();
+            let total_executors = executors.len();
+
+            tokio::select! {
+                _ = shutdown.cancelled() => info!("shutdown requested, stopping collectors..."),
+                _ = join_all(&mut collectors) => {
+                    info!("all collectors finished, stopping engine...");
+                    shutdown.cancel();
+                }
+            }
+            let started = Instant::now();
+
+            // Collectors stop as soon as the token is cancelled, which closes the
+            // event channel. Strategies then drain the remaining events and exit,
+            // which in turn closes the action channel for the executors. The
+            // whole shutdown shares the same deadline.
+            let deadline = tokio::time::Instant::now() + shutdown_timeout;
+            let mut aborted_collectors = 0;
+            if tokio::time::timeout_at(deadline, join_all(&mut collectors))
+                .await
+                .is_err()
+            {
+                aborted_collectors = collectors.len();
+                warn!(
+                    "shutdown timed out after {:?}, aborting {} collectors",
+                    shutdown_timeout, aborted_collectors
+                );
+                // Aborted collectors release their event senders, closing the
+                // event channel for the strategies, once they next yield.
+                collectors.abort_all();
+            }
+            let drained = tokio::time::timeout_at(deadline, async {
+                join_all(&mut strategies).await;
+                join_all(&mut executors).await;
+            })
+            .await;
+            if drained.is_err() {
+                warn!(
+                    "shutdown timed out after {:?}, aborting {} tasks",
+                    shutdown_timeout,
+                    strategies.len() + executors.len()
+                );
+            }
+
+            let shutdown_summary = ShutdownSummary {
+                collectors: total_collectors - aborted_collectors,
+                strategies: total_strategies - strategies.len(),
+                executors: total_executors - executors.len(),
+                aborted: aborted_collectors + strategies.len() + executors.len(),
+                events_drained: events_drained.load(Ordering::Relaxed),
+                actions_drained: actions_drained.load(Ordering::Relaxed),
+                elapsed: started.elapsed(),
+            };
+            strategies.abort_all();
+            executors.abort_all();
+            status.stop_all();
+
+            info!("engine stopped: {:?}", shutdown_summary);
+            summary.send_replace(Some(shutdown_summary));
+        });
+
         Ok(set)
     }
 }
+
+/// Waits for every task in the set to complete.
+async fn join_all(set: &mut JoinSet<()>) {
+    while set.join_next().await.is_some() {}
+}
@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Source of correlation IDs, unique across all engines in the process.
+static NEXT_CORRELATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// An event or action as it travels through the engine, along with the
+/// metadata needed to trace an action back to the event it came from.
+#[derive(Debug, Clone)]
+pub(crate) struct Envelope<T> {
+    /// Shared by an event and every action produced from it.
+    pub(crate) correlation_id: u64,
+
+    /// Name of the collector that emitted the originating event.
+    pub(crate) source: Arc<str>,
+
+    /// Name of the strategy that produced the action, if this is an action.
+    pub(crate) strategy: Option<Arc<str>>,
+
+    /// When the originating event was received from the collector.
+    pub(crate) received_at: Instant,
+
+    /// When the strategy produced the action, if this is an action.
+    pub(crate) produced_at: Option<Instant>,
+
+    pub(crate) payload: T,
+}
+
+impl<T> Envelope<T> {
+    /// Wraps an event freshly emitted by the given collector.
+    pub(crate) fn event(source: Arc<str>, payload: T) -> Self {
+        Self {
+            correlation_id: NEXT_CORRELATION_ID.fetch_add(1, Ordering::Relaxed),
+            source,
+            strategy: None,
+            received_at: Instant::now(),
+            produced_at: None,
+            payload,
+        }
+    }
+
+    /// Separates the payload from the metadata.
+    pub(crate) fn split(self) -> (Envelope<()>, T) {
+        let metadata = Envelope {
+            correlation_id: self.correlation_id,
+            source: self.source,
+            strategy: self.strategy,
+            received_at: self.received_at,
+            produced_at: self.produced_at,
+            payload: (),
+        };
+        (metadata, self.payload)
+    }
+
+    /// Wraps an action produced by the given strategy while processing this event.
+    pub(crate) fn action<A>(&self, strategy: Arc<str>, payload: A) -> Envelope<A> {
+        Envelope {
+            correlation_id: self.correlation_id,
+            source: self.source.clone(),
+            strategy: Some(strategy),
+            received_at: self.received_at,
+            produced_at: Some(Instant::now()),
+            payload,
+        }
+    }
+}
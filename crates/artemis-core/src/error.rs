@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use alloy::{
+    primitives::{U128, U256},
+    transports::TransportError,
+};
+
+/// A result type defaulting to the Artemis [Error](Error).
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The error type returned by the engine and by collectors, strategies and
+/// executors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A request to the node failed at the transport or RPC level.
+    #[error("transport error: {0}")]
+    Transport(#[from] TransportError),
+
+    /// A collector could not subscribe to its source.
+    #[error("failed to subscribe to {subscription}: {source}")]
+    Subscription {
+        /// The kind of subscription, e.g. `"blocks"`.
+        subscription: &'static str,
+        /// The underlying transport error.
+        #[source]
+        source: TransportError,
+    },
+
+    /// A strategy could not sync its state.
+    #[error("strategy {strategy} failed to sync state: {source}")]
+    StrategySync {
+        /// The name of the strategy.
+        strategy: String,
+        /// The error returned by the strategy.
+        #[source]
+        source: Box<Error>,
+    },
+
+    /// An action was rejected by the node it was submitted to.
+    #[error("action rejected: {0}")]
+    Rejected(#[from] Rejection),
+
+    /// A gas bid could not be computed or was refused by the executor's guards.
+    #[error("gas bid refused: {0}")]
+    Bid(#[from] BidError),
+
+    /// An operation did not complete within its time limit.
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// The engine is shutting down.
+    #[error("engine is shutting down")]
+    Shutdown,
+
+    /// Any other error.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// The reason an action was rejected by a node, or by its pre-flight simulation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Rejection {
+    /// The transaction nonce was already used.
+    #[error("nonce too low")]
+    NonceTooLow,
+
+    /// The transaction fees were too low to be accepted or to replace a
+    /// pending transaction.
+    #[error("transaction underpriced")]
+    Underpriced,
+
+    /// The sender cannot pay for the transaction.
+    #[error("insufficient funds")]
+    InsufficientFunds,
+
+    /// The transaction reverted, with the message returned by the node.
+    #[error("execution reverted: {0}")]
+    Reverted(String),
+
+    /// The simulated transaction did not increase the profit token balance
+    /// by the required amount.
+    #[error(
+        "profit token balance went from {before} to {after}, expected an increase of {min_delta}"
+    )]
+    InsufficientProfit {
+        /// The balance before the transaction.
+        before: U256,
+        /// The balance after the transaction.
+        after: U256,
+        /// The minimum expected increase.
+        min_delta: U256,
+    },
+}
+
+/// The reason a gas bid could not be computed or was refused.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BidError {
+    /// The transaction was estimated to use no gas.
+    #[error("gas usage is zero")]
+    ZeroGasUsage,
+
+    /// The share of the profit to bid is above 100%.
+    #[error("bid percentage {0} is above 100")]
+    InvalidBidPercentage(U128),
+
+    /// The bid does not fit in the numeric types involved.
+    #[error("arithmetic overflow computing the bid")]
+    Overflow,
+
+    /// The base fee per gas leaves nothing of the profit to bid with.
+    #[error("base fee {base_fee} is at or above the break-even gas price {breakeven_gas_price}")]
+    BaseFeeAboveBreakeven {
+        /// The next block's base fee.
+        base_fee: U128,
+        /// The gas price at which the whole profit is spent on gas.
+        breakeven_gas_price: U128,
+    },
+
+    /// The gas price is above the executor's maximum.
+    #[error("gas price {gas_price} is above the cap of {cap}")]
+    GasPriceAboveCap {
+        /// The gas price, or max fee per gas, of the transaction.
+        gas_price: u128,
+        /// The maximum gas price of the executor.
+        cap: u128,
+    },
+
+    /// The profit left after paying for gas is below the executor's minimum.
+    #[error(
+        "profit {total_profit} minus gas cost {gas_cost} is below the minimum of {min_net_profit}"
+    )]
+    ProfitBelowMinimum {
+        /// The total profit expected from the opportunity.
+        total_profit: U128,
+        /// The expected cost of the gas used by the transaction.
+        gas_cost: U128,
+        /// The minimum profit to keep after gas.
+        min_net_profit: U128,
+    },
+}
+
+impl Error {
+    /// Converts an error returned while estimating or submitting a transaction,
+    /// classifying known node rejections as [Error::Rejected].
+    pub fn from_submission(err: TransportError) -> Self {
+        let Some(payload) = err.as_error_resp() else {
+            return Self::Transport(err);
+        };
+        let message = payload.message.to_lowercase();
+        let rejection = if message.contains("nonce too low") {
+            Rejection::NonceTooLow
+        } else if message.contains("underpriced") || message.contains("less than block base fee") {
+            Rejection::Underpriced
+        } else if message.contains("insufficient funds") {
+            Rejection::InsufficientFunds
+        } else if message.contains("revert") {
+            Rejection::Reverted(payload.message.to_string())
+        } else {
+            return Self::Transport(err);
+        };
+        Self::Rejected(rejection)
+    }
+
+    /// Returns true if the error may be transient, i.e. the same action may
+    /// succeed if tried again: transport failures that are not an error
+    /// response from the node, failed subscriptions and timeouts.
+    ///
+    /// Error responses are answers from the node, e.g. "intrinsic gas too low",
+    /// which the same request would get again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Transport(err) => err.as_error_resp().is_none(),
+            Self::Subscription { .. } | Self::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the rejection reason if this error is a rejected action.
+    pub fn rejection(&self) -> Option<&Rejection> {
+        match self {
+            Self::Rejected(rejection) => Some(rejection),
+            _ => None,
+        }
+    }
+}
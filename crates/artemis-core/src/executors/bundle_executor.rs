@@ -0,0 +1,203 @@
+use crate::error::{Rejection, Result};
+use crate::executors::relay::{Relay, RelayClient};
+use crate::types::Executor;
+use alloy::{
+    eips::{eip2718::Encodable2718, BlockNumberOrTag},
+    network::{EthereumWallet, TransactionBuilder},
+    primitives::{Bytes, TxHash},
+    rpc::types::{
+        mev::{EthBundleHash, EthCallBundle, EthCallBundleResponse, EthSendBundle},
+        serde_helpers::WithOtherFields,
+        TransactionRequest,
+    },
+};
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::info;
+
+/// An executor that sends bundles to a Flashbots-style relay with
+/// `eth_sendBundle`, optionally simulating them first with `eth_callBundle`.
+pub struct BundleExecutor {
+    relay: RelayClient,
+    wallet: Option<EthereumWallet>,
+    simulate: bool,
+}
+
+/// The `eth_callBundle` simulation of a bundle.
+#[derive(Debug, Clone)]
+pub struct BundleSimulation {
+    pub response: EthCallBundleResponse,
+
+    /// The error reported for each transaction of the bundle, in order. Relays
+    /// set it for transactions that failed, including ones that failed
+    /// without reverting, e.g. with an invalid nonce.
+    pub errors: Vec<Option<String>>,
+}
+
+/// A transaction in a bundle.
+#[derive(Debug, Clone)]
+pub enum BundleTx {
+    /// An EIP-2718 encoded signed transaction.
+    Signed(Bytes),
+
+    /// A transaction to be signed by the executor's wallet. It must be
+    /// complete, i.e. set its nonce, gas limit, fees and chain ID.
+    Unsigned(Box<WithOtherFields<TransactionRequest>>),
+}
+
+/// A bundle of transactions to be included atomically, in order, in a block.
+#[derive(Debug, Clone, Default)]
+pub struct SubmitBundle {
+    pub txs: Vec<BundleTx>,
+
+    /// The block the bundle targets.
+    pub block_number: u64,
+
+    /// The earliest timestamp at which the bundle is valid.
+    pub min_timestamp: Option<u64>,
+
+    /// The latest timestamp at which the bundle is valid.
+    pub max_timestamp: Option<u64>,
+
+    /// Hashes of the transactions that are allowed to revert.
+    pub reverting_tx_hashes: Vec<TxHash>,
+}
+
+impl BundleTx {
+    /// Returns the EIP-2718 encoded transaction, signing it with the wallet if
+    /// it is unsigned.
+    pub async fn encode(&self, wallet: Option<&EthereumWallet>) -> Result<Bytes> {
+        match self {
+            Self::Signed(tx) => Ok(tx.clone()),
+            Self::Unsigned(tx) => {
+                let wallet = wallet.ok_or_else(|| {
+                    anyhow::anyhow!("cannot sign an unsigned transaction without a wallet")
+                })?;
+                let envelope = tx
+                    .inner
+                    .clone()
+                    .build(wallet)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("failed to sign transaction: {}", e))?;
+                Ok(envelope.encoded_2718().into())
+            }
+        }
+    }
+}
+
+impl SubmitBundle {
+    /// Returns the EIP-2718 encoded transactions of the bundle, signing the
+    /// unsigned ones with the wallet.
+    pub async fn encode_txs(&self, wallet: Option<&EthereumWallet>) -> Result<Vec<Bytes>> {
+        let mut txs = Vec::with_capacity(self.txs.len());
+        for tx in &self.txs {
+            txs.push(tx.encode(wallet).await?);
+        }
+        Ok(txs)
+    }
+
+    /// Returns the `eth_sendBundle` request for the bundle, signing its
+    /// unsigned transactions with the wallet.
+    pub async fn to_send_bundle(&self, wallet: Option<&EthereumWallet>) -> Result<EthSendBundle> {
+        Ok(EthSendBundle {
+            txs: self.encode_txs(wallet).await?,
+            block_number: self.block_number,
+            min_timestamp: self.min_timestamp,
+            max_timestamp: self.max_timestamp,
+            reverting_tx_hashes: self.reverting_tx_hashes.clone(),
+            replacement_uuid: None,
+        })
+    }
+}
+
+impl BundleExecutor {
+    pub fn new(relay: RelayClient) -> Self {
+        Self {
+            relay,
+            wallet: None,
+            simulate: false,
+        }
+    }
+
+    /// Signs the [unsigned](BundleTx::Unsigned) transactions of bundles with
+    /// the wallet, using the signer matching their `from`.
+    pub fn with_wallet(mut self, wallet: EthereumWallet) -> Self {
+        self.wallet = Some(wallet);
+        self
+    }
+
+    /// Simulates every bundle with `eth_callBundle` before sending it, and
+    /// rejects it with [Rejection::Reverted] if a transaction not allowed to
+    /// revert reverts or fails.
+    pub fn with_simulation(mut self) -> Self {
+        self.simulate = true;
+        self
+    }
+
+    /// Simulates the bundle on top of the latest block with `eth_callBundle`.
+    pub async fn call_bundle(&self, bundle: &SubmitBundle) -> Result<BundleSimulation> {
+        let request = EthCallBundle {
+            txs: bundle.encode_txs(self.wallet.as_ref()).await?,
+            block_number: bundle.block_number,
+            state_block_number: BlockNumberOrTag::Latest,
+            timestamp: bundle.min_timestamp,
+            ..Default::default()
+        };
+        let response: Value = self.relay.request("eth_callBundle", (request,)).await?;
+        let errors = response["results"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .map(|result| result["error"].as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let response = serde_json::from_value(response)
+            .map_err(|e| anyhow::anyhow!("invalid eth_callBundle response: {}", e))?;
+        Ok(BundleSimulation { response, errors })
+    }
+
+    /// Sends the bundle with `eth_sendBundle`, returning its hash.
+    pub async fn send_bundle(&self, bundle: &SubmitBundle) -> Result<EthBundleHash> {
+        let request = bundle.to_send_bundle(self.wallet.as_ref()).await?;
+        self.relay.send_bundle(request).await
+    }
+}
+
+#[async_trait]
+impl Executor<SubmitBundle> for BundleExecutor {
+    /// Send a bundle to the relay.
+    async fn execute(&self, action: SubmitBundle) -> Result<()> {
+        if self.simulate {
+            let simulation = self.call_bundle(&action).await?;
+            let failed = simulation
+                .response
+                .results
+                .iter()
+                .enumerate()
+                .map(|(i, result)| (result, simulation.errors.get(i).cloned().flatten()))
+                .find(|(result, error)| {
+                    (result.revert.is_some() || error.is_some())
+                        && !action.reverting_tx_hashes.contains(&result.tx_hash)
+                });
+            if let Some((result, error)) = failed {
+                return Err(Rejection::Reverted(match error {
+                    Some(error) => {
+                        format!("bundle transaction {} failed: {}", result.tx_hash, error)
+                    }
+                    None => format!("bundle transaction {} reverted", result.tx_hash),
+                })
+                .into());
+            }
+        }
+        let bundle_hash = self.send_bundle(&action).await?.bundle_hash;
+        info!(
+            "sent bundle {} for block {} to {}",
+            bundle_hash,
+            action.block_number,
+            self.relay.url()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::error::{Error, Result};
+use crate::types::Executor;
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+/// RetryExecutor is a wrapper around an [Executor](Executor) that retries
+/// failed actions with exponential backoff.
+///
+/// By default, actions are retried up to 3 times if they failed with a
+/// [retryable](Error::is_retryable) error, waiting 100ms before the first
+/// retry and doubling the delay up to 5s.
+pub struct RetryExecutor<A> {
+    executor: Box<dyn Executor<A>>,
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    retry_if: Box<dyn Fn(&Error) -> bool + Send + Sync>,
+}
+
+impl<A> RetryExecutor<A> {
+    pub fn new(executor: Box<dyn Executor<A>>) -> Self {
+        Self {
+            executor,
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            retry_if: Box::new(Error::is_retryable),
+        }
+    }
+
+    /// Sets how many times an action is retried after its first attempt.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the delay before the first retry, doubled after every retry up to
+    /// `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Only retries actions whose error matches the predicate, instead of
+    /// [retryable](Error::is_retryable) errors.
+    pub fn with_retry_if<F>(mut self, retry_if: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Box::new(retry_if);
+        self
+    }
+}
+
+#[async_trait]
+impl<A> Executor<A> for RetryExecutor<A>
+where
+    A: Clone + Send + Sync + 'static,
+{
+    async fn execute(&self, action: A) -> Result<()> {
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+        loop {
+            match self.executor.execute(action.clone()).await {
+                Err(e) if retries < self.max_retries && (self.retry_if)(&e) => {
+                    retries += 1;
+                    warn!(
+                        "action failed, retrying in {:?} ({}/{}): {}",
+                        backoff, retries, self.max_retries, e
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2).min(self.max_backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// TimeoutExecutor is a wrapper around an [Executor](Executor) that fails
+/// actions with [Error::Timeout] if they take longer than a time limit.
+pub struct TimeoutExecutor<A> {
+    executor: Box<dyn Executor<A>>,
+    timeout: Duration,
+}
+
+impl<A> TimeoutExecutor<A> {
+    pub fn new(executor: Box<dyn Executor<A>>, timeout: Duration) -> Self {
+        Self { executor, timeout }
+    }
+}
+
+#[async_trait]
+impl<A> Executor<A> for TimeoutExecutor<A>
+where
+    A: Send + Sync + 'static,
+{
+    async fn execute(&self, action: A) -> Result<()> {
+        tokio::time::timeout(self.timeout, self.executor.execute(action))
+            .await
+            .map_err(|_| Error::Timeout(self.timeout))?
+    }
+}
+
+/// RateLimitedExecutor is a wrapper around an [Executor](Executor) that limits
+/// how often actions are executed with a token bucket.
+///
+/// The bucket holds up to `burst` tokens and gains one every `interval`. Every
+/// action takes a token, waiting for one if the bucket is empty. Waiting
+/// actions are executed in the order they arrived.
+pub struct RateLimitedExecutor<A> {
+    executor: Box<dyn Executor<A>>,
+    burst: u32,
+    interval: Duration,
+    bucket: tokio::sync::Mutex<TokenBucket>,
+}
+
+struct TokenBucket {
+    tokens: u32,
+    refilled_at: Instant,
+}
+
+impl<A> RateLimitedExecutor<A> {
+    /// Creates an executor allowing bursts of `burst` actions, and one action
+    /// per `interval` on average. A `burst` of 0 is treated as 1.
+    pub fn new(executor: Box<dyn Executor<A>>, burst: u32, interval: Duration) -> Self {
+        let burst = burst.max(1);
+        Self {
+            executor,
+            burst,
+            interval,
+            bucket: tokio::sync::Mutex::new(TokenBucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token from the bucket, waiting for one if it is empty.
+    async fn acquire(&self) {
+        // The lock is held while waiting so that actions get tokens in order.
+        let mut bucket = self.bucket.lock().await;
+        loop {
+            let elapsed = bucket.refilled_at.elapsed();
+            let new_tokens = match self.interval.as_nanos() {
+                0 => u128::from(self.burst),
+                interval => elapsed.as_nanos() / interval,
+            };
+            if new_tokens > 0 {
+                let tokens = u128::from(bucket.tokens) + new_tokens;
+                if tokens >= u128::from(self.burst) {
+                    bucket.tokens = self.burst;
+                    bucket.refilled_at = Instant::now();
+                } else {
+                    bucket.tokens = tokens as u32;
+                    bucket.refilled_at += self.interval * new_tokens as u32;
+                }
+            }
+            if bucket.tokens > 0 {
+                bucket.tokens -= 1;
+                return;
+            }
+            tokio::time::sleep(self.interval.saturating_sub(elapsed)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<A> Executor<A> for RateLimitedExecutor<A>
+where
+    A: Send + Sync + 'static,
+{
+    async fn execute(&self, action: A) -> Result<()> {
+        self.acquire().await;
+        self.executor.execute(action).await
+    }
+}
+
+/// DedupExecutor is a wrapper around an [Executor](Executor) that drops
+/// actions identical to one executed within a time window.
+///
+/// Actions are identified by the key returned by `f`. An action whose
+/// execution failed does not count, so it can be retried right away.
+pub struct DedupExecutor<A, K, F> {
+    executor: Box<dyn Executor<A>>,
+    window: Duration,
+    f: F,
+    seen: Mutex<HashMap<K, Instant>>,
+}
+
+impl<A, K, F> DedupExecutor<A, K, F> {
+    pub fn new(executor: Box<dyn Executor<A>>, window: Duration, f: F) -> Self {
+        Self {
+            executor,
+            window,
+            f,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl<A, K, F> Executor<A> for DedupExecutor<A, K, F>
+where
+    A: Send + Sync + 'static,
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    F: Fn(&A) -> K + Send + Sync + 'static,
+{
+    async fn execute(&self, action: A) -> Result<()> {
+        let key = (self.f)(&action);
+        let now = Instant::now();
+        {
+            let mut seen = self.seen.lock().unwrap();
+            seen.retain(|_, executed_at| now.duration_since(*executed_at) < self.window);
+            if seen.contains_key(&key) {
+                debug!("dropping duplicate action");
+                return Ok(());
+            }
+            seen.insert(key.clone(), now);
+        }
+        let result = self.executor.execute(action).await;
+        if result.is_err() {
+            let mut seen = self.seen.lock().unwrap();
+            if seen.get(&key) == Some(&now) {
+                seen.remove(&key);
+            }
+        }
+        result
+    }
+}
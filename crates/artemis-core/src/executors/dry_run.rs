@@ -0,0 +1,50 @@
+use std::sync::{Arc, Mutex};
+
+use alloy::{
+    primitives::{Bytes, TxHash},
+    rpc::types::{serde_helpers::WithOtherFields, TransactionRequest},
+};
+use tracing::info;
+
+/// Records the transactions an executor in dry-run mode would have sent.
+///
+/// The recorder is cheap to clone, and clones share the same records, so a
+/// clone can be kept to inspect what the executor did.
+#[derive(Debug, Clone, Default)]
+pub struct DryRun {
+    txs: Arc<Mutex<Vec<DryRunTx>>>,
+}
+
+/// A transaction that would have been sent.
+#[derive(Debug, Clone)]
+pub struct DryRunTx {
+    /// The final transaction, with its nonce and fees.
+    pub tx: WithOtherFields<TransactionRequest>,
+
+    /// The EIP-2718 encoded signed transaction, if the executor signs locally.
+    pub raw: Option<Bytes>,
+
+    /// The hash of the signed transaction, if the executor signs locally.
+    pub hash: Option<TxHash>,
+}
+
+impl DryRun {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded transactions, oldest first.
+    pub fn txs(&self) -> Vec<DryRunTx> {
+        self.txs.lock().unwrap().clone()
+    }
+
+    /// Removes and returns the recorded transactions, oldest first.
+    pub fn take(&self) -> Vec<DryRunTx> {
+        std::mem::take(&mut *self.txs.lock().unwrap())
+    }
+
+    pub(crate) fn record(&self, tx: DryRunTx) {
+        info!("dry run, not sending transaction: {:?}", tx);
+        self.txs.lock().unwrap().push(tx);
+    }
+}
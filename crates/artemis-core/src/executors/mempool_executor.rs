@@ -1,33 +1,87 @@
-use std::{
-    ops::{Div, Mul},
-    sync::Arc,
-};
+use std::sync::Arc;
 
+use crate::error::{BidError, Error, Rejection, Result};
+use crate::executors::{
+    dry_run::{DryRun, DryRunTx},
+    nonce_manager::NonceManager,
+    simulation::Simulation,
+    tx_tracker::TxTracker,
+};
 use crate::types::Executor;
 use alloy::{
-    network::{AnyNetwork, TransactionBuilder},
-    primitives::U128,
+    eips::{eip1559::Eip1559Estimation, eip2718::Encodable2718, BlockNumberOrTag},
+    network::{AnyNetwork, EthereumWallet, NetworkWallet, TransactionBuilder},
+    primitives::{keccak256, Address, Bytes, U128, U256},
     providers::{DynProvider, Provider},
     rpc::types::{serde_helpers::WithOtherFields, TransactionRequest},
 };
-use anyhow::{Context, Result};
 use async_trait::async_trait;
+use tokio::sync::OnceCell;
+use tracing::warn;
 
 /// An executor that sends transactions to the mempool.
+///
+/// Nonces of transactions with a sender are assigned by a [NonceManager],
+/// which is resynced from the node if it rejects a nonce.
+///
+/// Without a [wallet](MempoolExecutor::with_wallet), transactions are sent
+/// with `eth_sendTransaction` and signed by the node, which must manage the
+/// sender account.
 pub struct MempoolExecutor {
     client: Arc<DynProvider<AnyNetwork>>,
+    wallet: Option<EthereumWallet>,
+    chain_id: OnceCell<u64>,
+    nonces: NonceManager,
+    pricing: GasPricing,
+    fee_caps: FeeCaps,
+    max_gas_price: Option<u128>,
+    min_net_profit: U128,
+    simulation: Option<Simulation>,
+    tracker: Option<TxTracker>,
+    dry_run: Option<DryRun>,
 }
 
 /// Information about the gas bid for a transaction.
+///
+/// With [GasPricing::Legacy] the bid is the gas price. With
+/// [GasPricing::Eip1559] the base fee is paid first, and the bid is the share
+/// of the profit left after it, paid as the priority fee, so that a 100% bid
+/// spends the whole profit in both cases.
 #[derive(Debug, Clone)]
 pub struct GasBidInfo {
     /// Total profit expected from opportunity
     pub total_profit: U128,
 
-    /// Percentage of bid profit to use for gas
+    /// Percentage of bid profit to use for gas, at most 100
     pub bid_percentage: U128,
 }
 
+/// How the [MempoolExecutor] prices the transactions it sends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GasPricing {
+    /// Type-2 transactions, with the max fee derived from the next block's
+    /// base fee.
+    #[default]
+    Eip1559,
+
+    /// Legacy transactions with a single gas price.
+    Legacy,
+}
+
+/// Upper bounds for the fees of type-2 transactions, in wei per gas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeCaps {
+    /// Cap for `max_priority_fee_per_gas`, or `None` for no cap.
+    pub max_priority_fee_per_gas: Option<u128>,
+
+    /// Cap for `max_fee_per_gas`, or `None` for no cap.
+    pub max_fee_per_gas: Option<u128>,
+}
+
+/// A transaction to send to the mempool.
+///
+/// If the transaction already sets a nonce it is sent as is, which replaces a
+/// pending transaction with the same nonce when its fees are high enough.
 #[derive(Debug, Clone)]
 pub struct SubmitTxToMempool {
     pub tx: WithOtherFields<TransactionRequest>,
@@ -36,38 +90,476 @@ pub struct SubmitTxToMempool {
 
 impl MempoolExecutor {
     pub fn new(client: Arc<DynProvider<AnyNetwork>>) -> Self {
-        Self { client }
+        let nonces = NonceManager::new(client.clone());
+        Self {
+            client,
+            wallet: None,
+            chain_id: OnceCell::new(),
+            nonces,
+            pricing: GasPricing::default(),
+            fee_caps: FeeCaps::default(),
+            max_gas_price: None,
+            min_net_profit: U128::ZERO,
+            simulation: None,
+            tracker: None,
+            dry_run: None,
+        }
+    }
+
+    /// Signs transactions locally with the wallet and sends them with
+    /// `eth_sendRawTransaction`.
+    ///
+    /// Transactions are signed by the wallet's signer matching their `from`,
+    /// and transactions without a `from` are sent from the wallet's default
+    /// signer. See [signers](crate::executors::signers) to load signers.
+    pub fn with_wallet(mut self, wallet: EthereumWallet) -> Self {
+        self.wallet = Some(wallet);
+        self
+    }
+
+    /// Uses the given nonce manager, e.g. to share nonces with other executors
+    /// sending from the same accounts.
+    pub fn with_nonce_manager(mut self, nonces: NonceManager) -> Self {
+        self.nonces = nonces;
+        self
+    }
+
+    /// Sets how transactions are priced. Defaults to [GasPricing::Eip1559].
+    pub fn with_gas_pricing(mut self, pricing: GasPricing) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// Caps the fees of type-2 transactions.
+    pub fn with_fee_caps(mut self, caps: FeeCaps) -> Self {
+        self.fee_caps = caps;
+        self
+    }
+
+    /// Simulates every transaction against the pending block before sending
+    /// it, rejecting the action if the simulation fails.
+    pub fn with_simulation(mut self, simulation: Simulation) -> Self {
+        self.simulation = Some(simulation);
+        self
+    }
+
+    /// Watches every sent transaction with the tracker, which publishes its
+    /// outcome to the tracker's collector.
+    pub fn with_tracker(mut self, tracker: TxTracker) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    /// Runs the executor in dry-run mode: transactions are estimated, priced,
+    /// simulated and signed as usual, then recorded by `dry_run` instead of
+    /// being sent.
+    ///
+    /// Nonces are still reserved, so consecutive transactions from a sender
+    /// get consecutive nonces as if the previous ones had been sent.
+    pub fn with_dry_run(mut self, dry_run: DryRun) -> Self {
+        self.dry_run = Some(dry_run);
+        self
+    }
+
+    /// Refuses to send transactions whose gas price, or max fee per gas, is
+    /// above the cap, failing with [BidError::GasPriceAboveCap]. Unlike
+    /// [FeeCaps], the fees are not lowered to fit.
+    pub fn with_max_gas_price(mut self, cap: u128) -> Self {
+        self.max_gas_price = Some(cap);
+        self
+    }
+
+    /// Refuses to send transactions with a [GasBidInfo] whose profit, after
+    /// paying for gas at the expected price, would be below the minimum,
+    /// failing with [BidError::ProfitBelowMinimum]. Defaults to zero, so a bid
+    /// never costs more than the profit.
+    ///
+    /// The expected price of a type-2 transaction is the next block's base fee
+    /// plus its priority fee, limited by its max fee.
+    pub fn with_min_net_profit(mut self, min_net_profit: U128) -> Self {
+        self.min_net_profit = min_net_profit;
+        self
+    }
+
+    /// Checks the final gas price of a transaction using `gas_usage` gas
+    /// against the executor's guards. The cap applies to the highest price the
+    /// transaction may pay, and the profit is checked at the price it is
+    /// expected to pay.
+    fn check_bid(
+        &self,
+        gas_usage: u64,
+        gas_price: u128,
+        expected_gas_price: u128,
+        gas_bid_info: Option<&GasBidInfo>,
+    ) -> Result<(), BidError> {
+        if let Some(cap) = self.max_gas_price.filter(|cap| gas_price > *cap) {
+            return Err(BidError::GasPriceAboveCap { gas_price, cap });
+        }
+        if let Some(gas_bid_info) = gas_bid_info {
+            let gas_cost = U128::from(gas_usage)
+                .checked_mul(U128::from(expected_gas_price))
+                .ok_or(BidError::Overflow)?;
+            let enough = gas_bid_info
+                .total_profit
+                .checked_sub(gas_cost)
+                .is_some_and(|net_profit| net_profit >= self.min_net_profit);
+            if !enough {
+                return Err(BidError::ProfitBelowMinimum {
+                    total_profit: gas_bid_info.total_profit,
+                    gas_cost,
+                    min_net_profit: self.min_net_profit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the fees of a type-2 transaction using `gas_usage` gas, along
+    /// with the next block's base fee.
+    async fn eip1559_fees(
+        &self,
+        gas_usage: u64,
+        gas_bid_info: Option<&GasBidInfo>,
+    ) -> Result<(Eip1559Estimation, u128)> {
+        let base_fee = self
+            .client
+            .get_fee_history(1, BlockNumberOrTag::Latest, &[])
+            .await?
+            .next_block_base_fee()
+            .ok_or_else(|| anyhow::anyhow!("node did not report the next block's base fee"))?;
+
+        let priority_fee = match gas_bid_info {
+            // The base fee is burned, so the bid is the share of the profit
+            // left after it, going to the validator as the priority fee.
+            Some(gas_bid_info) => {
+                bid_gas_price(gas_bid_info, gas_usage, U128::from(base_fee))?.to()
+            }
+            None => self.client.get_max_priority_fee_per_gas().await?,
+        };
+        let max_priority_fee_per_gas = self
+            .fee_caps
+            .max_priority_fee_per_gas
+            .map_or(priority_fee, |cap| priority_fee.min(cap));
+
+        // The max fee is derived from the capped priority fee, so that a bid
+        // never pays more than the profit, even if the base fee rises.
+        let max_fee_per_gas = match gas_bid_info {
+            Some(_) => base_fee
+                .checked_add(max_priority_fee_per_gas)
+                .ok_or(BidError::Overflow)?,
+            // Leave room for the base fee to double before inclusion.
+            None => base_fee
+                .saturating_mul(2)
+                .saturating_add(max_priority_fee_per_gas),
+        };
+        let max_fee_per_gas = self
+            .fee_caps
+            .max_fee_per_gas
+            .map_or(max_fee_per_gas, |cap| max_fee_per_gas.min(cap));
+        let max_priority_fee_per_gas = max_priority_fee_per_gas.min(max_fee_per_gas);
+        Ok((
+            Eip1559Estimation {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            },
+            base_fee,
+        ))
+    }
+
+    /// Sends a replacement for the transaction, paying at least the minimum
+    /// replacement bump over its fees and at least the current market fees.
+    async fn replace(&self, mut tx: WithOtherFields<TransactionRequest>) -> Result<()> {
+        match self.pricing {
+            GasPricing::Eip1559 => {
+                let (market, _) = self.eip1559_fees(0, None).await?;
+                let max_fee_per_gas = replacement_fee(tx.max_fee_per_gas, market.max_fee_per_gas);
+                let max_priority_fee_per_gas =
+                    replacement_fee(tx.max_priority_fee_per_gas, market.max_priority_fee_per_gas);
+                // The priority fee must be bumped too, so raise the max fee
+                // rather than lowering the priority fee.
+                let max_fee_per_gas = max_fee_per_gas.max(max_priority_fee_per_gas);
+                self.check_bid(0, max_fee_per_gas, max_fee_per_gas, None)?;
+                tx.gas_price = None;
+                tx.set_max_fee_per_gas(max_fee_per_gas);
+                tx.set_max_priority_fee_per_gas(max_priority_fee_per_gas);
+            }
+            GasPricing::Legacy => {
+                let market = self.client.get_gas_price().await?;
+                let gas_price = replacement_fee(tx.gas_price, market);
+                self.check_bid(0, gas_price, gas_price, None)?;
+                tx.max_fee_per_gas = None;
+                tx.max_priority_fee_per_gas = None;
+                tx.set_gas_price(gas_price);
+            }
+        }
+        self.send(tx).await
+    }
+
+    async fn send(&self, mut tx: WithOtherFields<TransactionRequest>) -> Result<()> {
+        let raw = match &self.wallet {
+            Some(wallet) => {
+                if tx.chain_id.is_none() {
+                    let chain_id = self
+                        .chain_id
+                        .get_or_try_init(|| self.client.get_chain_id())
+                        .await?;
+                    tx.set_chain_id(*chain_id);
+                }
+                Some(sign(wallet, tx.clone()).await?)
+            }
+            None => None,
+        };
+        let (sender, nonce) = (tx.from, tx.nonce);
+
+        if let Some(dry_run) = &self.dry_run {
+            if let (Some(sender), Some(nonce)) = (sender, nonce) {
+                self.nonces.record_sent(sender, nonce, tx.clone());
+            }
+            dry_run.record(DryRunTx {
+                tx,
+                hash: raw.as_ref().map(keccak256),
+                raw,
+            });
+            return Ok(());
+        }
+
+        let pending = match raw {
+            Some(raw) => self.client.send_raw_transaction(&raw).await,
+            None => self.client.send_transaction(tx.clone()).await,
+        }
+        .map_err(Error::from_submission)?;
+        if let (Some(sender), Some(nonce)) = (sender, nonce) {
+            self.nonces.record_sent(sender, nonce, tx);
+        }
+        if let Some(tracker) = &self.tracker {
+            tracker.track(*pending.tx_hash(), sender, nonce);
+        }
+        Ok(())
+    }
+
+    /// Updates the nonce manager after a transaction with the reserved nonce
+    /// failed to be sent.
+    async fn recover_nonce(&self, sender: Address, nonce: u64, error: &Error) {
+        match error {
+            Error::Rejected(Rejection::NonceTooLow) => {
+                self.resync_nonce(sender).await;
+            }
+            // The reserved nonce was not used. Other nonces of the sender may
+            // have been reserved since, in which case they must not be reused.
+            _ => {
+                self.nonces.release(sender, nonce).await;
+            }
+        }
+    }
+
+    /// Resyncs the nonce of the sender, logging failures instead of returning
+    /// them so that callers report the error that made them resync.
+    async fn resync_nonce(&self, sender: Address) -> bool {
+        match self.nonces.resync(sender).await {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("failed to resync the nonce of {}: {}", sender, e);
+                false
+            }
+        }
     }
 }
 
+/// Signs the transaction with the wallet, returning it EIP-2718 encoded.
+async fn sign(wallet: &EthereumWallet, tx: WithOtherFields<TransactionRequest>) -> Result<Bytes> {
+    let envelope = tx
+        .build(wallet)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to sign transaction: {}", e))?;
+    Ok(envelope.encoded_2718().into())
+}
+
+/// Resubmits a transaction previously sent by a [MempoolExecutor] sharing the
+/// same [NonceManager], with the same nonce and higher fees.
+///
+/// The fees are bumped by at least 12.5%, which satisfies the replacement
+/// rules of common clients, and are at least the current market fees.
+#[derive(Debug, Clone)]
+pub struct SpeedUpTx {
+    pub sender: Address,
+    pub nonce: u64,
+}
+
+/// Cancels a pending transaction by replacing it with a zero-value transfer
+/// from the sender to itself.
+///
+/// If a transaction sent with the nonce is known, the fees are bumped as for
+/// a [SpeedUpTx]. Otherwise the current market fees are used.
+#[derive(Debug, Clone)]
+pub struct CancelTx {
+    pub sender: Address,
+    pub nonce: u64,
+}
+
 #[async_trait]
 impl Executor<SubmitTxToMempool> for MempoolExecutor {
     /// Send a transaction to the mempool.
     async fn execute(&self, mut action: SubmitTxToMempool) -> Result<()> {
+        if let Some(wallet) = &self.wallet {
+            if action.tx.from.is_none() {
+                let sender = NetworkWallet::<AnyNetwork>::default_signer_address(wallet);
+                action.tx.set_from(sender);
+            }
+        }
         let gas_usage = self
             .client
             .estimate_gas(&action.tx)
             .await
-            .context("Error estimating gas usage: {}")?;
-
-        let bid_gas_price: U128;
-        if let Some(gas_bid_info) = action.gas_bid_info {
-            // gas price at which we'd break even, meaning 100% of profit goes to validator
-            let breakeven_gas_price: U128 = gas_bid_info.total_profit / U128::from(gas_usage);
-            // gas price corresponding to bid percentage
-            bid_gas_price = breakeven_gas_price
-                .mul(U128::from(gas_bid_info.bid_percentage))
-                .div(U128::from(100));
-        } else {
-            bid_gas_price = U128::from(
-                self.client
-                    .get_gas_price()
-                    .await
-                    .context("Error getting gas price: {}")?,
-            );
-        }
-        action.tx.set_gas_price(bid_gas_price.to());
-        let _ = self.client.send_transaction(action.tx).await?;
-        Ok(())
+            .map_err(Error::from_submission)?;
+        if self.wallet.is_some() && action.tx.gas.is_none() {
+            action.tx.set_gas_limit(gas_usage);
+        }
+        if let Some(simulation) = &self.simulation {
+            simulation.check(&self.client, &action.tx).await?;
+        }
+
+        match self.pricing {
+            GasPricing::Eip1559 => {
+                let (fees, base_fee) = self
+                    .eip1559_fees(gas_usage, action.gas_bid_info.as_ref())
+                    .await?;
+                let expected_gas_price = base_fee
+                    .saturating_add(fees.max_priority_fee_per_gas)
+                    .min(fees.max_fee_per_gas);
+                self.check_bid(
+                    gas_usage,
+                    fees.max_fee_per_gas,
+                    expected_gas_price,
+                    action.gas_bid_info.as_ref(),
+                )?;
+                action.tx.gas_price = None;
+                action.tx.set_max_fee_per_gas(fees.max_fee_per_gas);
+                action
+                    .tx
+                    .set_max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+            }
+            GasPricing::Legacy => {
+                let gas_price = match &action.gas_bid_info {
+                    Some(gas_bid_info) => bid_gas_price(gas_bid_info, gas_usage, U128::ZERO)?.to(),
+                    None => self.client.get_gas_price().await?,
+                };
+                self.check_bid(
+                    gas_usage,
+                    gas_price,
+                    gas_price,
+                    action.gas_bid_info.as_ref(),
+                )?;
+                action.tx.max_fee_per_gas = None;
+                action.tx.max_priority_fee_per_gas = None;
+                action.tx.set_gas_price(gas_price);
+            }
+        }
+
+        let sender = match (action.tx.from, action.tx.nonce) {
+            (Some(sender), None) => sender,
+            // Explicit nonces and node-filled senders are left untouched.
+            _ => return self.send(action.tx).await,
+        };
+        let nonce = self.nonces.next_nonce(sender).await?;
+        action.tx.set_nonce(nonce);
+        match self.send(action.tx.clone()).await {
+            Err(Error::Rejected(Rejection::NonceTooLow)) => {
+                // Our view of the nonce is stale, e.g. because the account was
+                // used elsewhere. Resync and try once more.
+                if !self.resync_nonce(sender).await {
+                    return Err(Error::Rejected(Rejection::NonceTooLow));
+                }
+                let nonce = self.nonces.next_nonce(sender).await?;
+                action.tx.set_nonce(nonce);
+                let result = self.send(action.tx).await;
+                if let Err(e) = &result {
+                    self.recover_nonce(sender, nonce, e).await;
+                }
+                result
+            }
+            Err(e) => {
+                self.recover_nonce(sender, nonce, &e).await;
+                Err(e)
+            }
+            Ok(()) => Ok(()),
+        }
     }
 }
+
+/// Returns the price per gas corresponding to the bid for a transaction using
+/// `gas_usage` gas, paid on top of `base_fee`.
+fn bid_gas_price(
+    gas_bid_info: &GasBidInfo,
+    gas_usage: u64,
+    base_fee: U128,
+) -> Result<U128, BidError> {
+    if gas_bid_info.bid_percentage > U128::from(100) {
+        return Err(BidError::InvalidBidPercentage(gas_bid_info.bid_percentage));
+    }
+    if gas_usage == 0 {
+        return Err(BidError::ZeroGasUsage);
+    }
+    // gas price at which we'd break even, meaning 100% of profit goes to validator
+    let breakeven_gas_price = gas_bid_info.total_profit / U128::from(gas_usage);
+    // the part of it left after the base fee is burned
+    let biddable_gas_price = match breakeven_gas_price.checked_sub(base_fee) {
+        Some(price) if !price.is_zero() => price,
+        _ => {
+            return Err(BidError::BaseFeeAboveBreakeven {
+                base_fee,
+                breakeven_gas_price,
+            })
+        }
+    };
+    // gas price corresponding to bid percentage
+    let bid_gas_price = biddable_gas_price
+        .checked_mul(gas_bid_info.bid_percentage)
+        .ok_or(BidError::Overflow)?;
+    Ok(bid_gas_price / U128::from(100))
+}
+
+#[async_trait]
+impl Executor<SpeedUpTx> for MempoolExecutor {
+    /// Resubmit a pending transaction with higher fees.
+    async fn execute(&self, action: SpeedUpTx) -> Result<()> {
+        let tx = self
+            .nonces
+            .sent(action.sender, action.nonce)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no transaction sent by {} with nonce {}",
+                    action.sender,
+                    action.nonce
+                )
+            })?;
+        self.replace(tx).await
+    }
+}
+
+#[async_trait]
+impl Executor<CancelTx> for MempoolExecutor {
+    /// Replace a pending transaction with a zero-value self-transfer.
+    async fn execute(&self, action: CancelTx) -> Result<()> {
+        let mut tx = TransactionRequest::default()
+            .with_from(action.sender)
+            .with_to(action.sender)
+            .with_value(U256::ZERO)
+            .with_nonce(action.nonce)
+            .with_gas_limit(21_000);
+        if let Some(previous) = self.nonces.sent(action.sender, action.nonce) {
+            tx.gas_price = previous.gas_price;
+            tx.max_fee_per_gas = previous.max_fee_per_gas;
+            tx.max_priority_fee_per_gas = previous.max_priority_fee_per_gas;
+        }
+        self.replace(WithOtherFields::new(tx)).await
+    }
+}
+
+/// Returns the fee for a replacement: the previous fee bumped by 12.5%,
+/// rounded up, or the market fee if higher.
+fn replacement_fee(previous: Option<u128>, market: u128) -> u128 {
+    previous
+        .map(|fee| fee.saturating_add(fee.div_ceil(8)))
+        .unwrap_or_default()
+        .max(market)
+}
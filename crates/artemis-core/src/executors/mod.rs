@@ -4,3 +4,39 @@
 //!
 /// This executor submits transactions to the public mempool.
 pub mod mempool_executor;
+
+/// This executor submits bundles to a Flashbots-style relay.
+pub mod bundle_executor;
+
+/// This executor submits bundles and private transactions to many relays at
+/// once.
+pub mod multi_relay_executor;
+
+/// This module contains wrappers adding retries, timeouts, rate limiting and
+/// deduplication to any executor.
+pub mod combinators;
+
+/// This module contains the [DryRun](dry_run::DryRun) recorder of
+/// transactions that executors in dry-run mode would have sent.
+pub mod dry_run;
+
+/// This module contains the [NonceManager](nonce_manager::NonceManager) used to
+/// assign nonces to transactions submitted by executors.
+pub mod nonce_manager;
+
+/// This module contains helpers to load the signers of an
+/// [EthereumWallet](alloy::network::EthereumWallet) used to sign transactions
+/// locally.
+pub mod signers;
+
+/// This module contains the pre-flight [Simulation](simulation::Simulation) of
+/// transactions before they are submitted.
+pub mod simulation;
+
+/// This module contains the [TxTracker](tx_tracker::TxTracker) watching
+/// submitted transactions until they land.
+pub mod tx_tracker;
+
+/// This module contains the [RelayClient](relay::RelayClient) used to send
+/// authenticated requests to relays and builders.
+pub mod relay;
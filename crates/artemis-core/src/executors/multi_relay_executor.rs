@@ -0,0 +1,202 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use crate::error::{Error, Result};
+use crate::executors::bundle_executor::{BundleTx, SubmitBundle};
+use crate::executors::relay::Relay;
+use crate::types::Executor;
+use alloy::{
+    network::EthereumWallet,
+    primitives::TxHash,
+    rpc::types::mev::{EthBundleHash, PrivateTransactionPreferences, PrivateTransactionRequest},
+};
+use async_trait::async_trait;
+use futures::future::join_all;
+use tracing::{info, warn};
+
+/// An executor that sends the same bundle or private transaction to many
+/// relays and builders at once.
+///
+/// Every relay is given its own timeout, and the result of every relay is
+/// logged and returned by [send_bundle](MultiRelayExecutor::send_bundle) and
+/// [send_private_tx](MultiRelayExecutor::send_private_tx). An action only fails
+/// if no relay accepted it.
+pub struct MultiRelayExecutor {
+    relays: Vec<RelayEntry>,
+    wallet: Option<EthereumWallet>,
+    timeout: Duration,
+}
+
+struct RelayEntry {
+    relay: Box<dyn Relay>,
+    timeout: Option<Duration>,
+}
+
+/// A transaction to be sent privately to relays with
+/// `eth_sendPrivateTransaction`.
+#[derive(Debug, Clone)]
+pub struct SubmitPrivateTx {
+    pub tx: BundleTx,
+
+    /// The last block the transaction may be included in.
+    pub max_block_number: Option<u64>,
+
+    /// Preferences passed to the relays.
+    pub preferences: PrivateTransactionPreferences,
+}
+
+/// The result of sending a bundle or private transaction to a relay.
+#[derive(Debug)]
+pub struct RelayResult<T> {
+    /// The name of the relay.
+    pub relay: String,
+
+    /// The response of the relay, or the error it failed with.
+    pub result: Result<T>,
+
+    /// How long the relay took to respond.
+    pub latency: Duration,
+}
+
+impl MultiRelayExecutor {
+    pub fn new() -> Self {
+        Self {
+            relays: vec![],
+            wallet: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Adds a relay using the default timeout.
+    pub fn with_relay(mut self, relay: impl Relay + 'static) -> Self {
+        self.relays.push(RelayEntry {
+            relay: Box::new(relay),
+            timeout: None,
+        });
+        self
+    }
+
+    /// Adds a relay with its own timeout.
+    pub fn with_relay_timeout(mut self, relay: impl Relay + 'static, timeout: Duration) -> Self {
+        self.relays.push(RelayEntry {
+            relay: Box::new(relay),
+            timeout: Some(timeout),
+        });
+        self
+    }
+
+    /// Sets how long relays are given to respond. Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Signs the [unsigned](BundleTx::Unsigned) transactions with the wallet,
+    /// using the signer matching their `from`.
+    pub fn with_wallet(mut self, wallet: EthereumWallet) -> Self {
+        self.wallet = Some(wallet);
+        self
+    }
+
+    /// Sends the bundle to every relay concurrently and returns their results,
+    /// in the order the relays were added.
+    pub async fn send_bundle(
+        &self,
+        bundle: &SubmitBundle,
+    ) -> Result<Vec<RelayResult<EthBundleHash>>> {
+        let request = bundle.to_send_bundle(self.wallet.as_ref()).await?;
+        Ok(self
+            .fan_out(|relay| relay.send_bundle(request.clone()))
+            .await)
+    }
+
+    /// Sends the private transaction to every relay concurrently and returns
+    /// their results, in the order the relays were added.
+    pub async fn send_private_tx(&self, tx: &SubmitPrivateTx) -> Result<Vec<RelayResult<TxHash>>> {
+        let request = PrivateTransactionRequest {
+            tx: tx.tx.encode(self.wallet.as_ref()).await?,
+            max_block_number: tx.max_block_number,
+            preferences: tx.preferences.clone(),
+        };
+        Ok(self
+            .fan_out(|relay| relay.send_private_transaction(request.clone()))
+            .await)
+    }
+
+    async fn fan_out<'a, T, F, Fut>(&'a self, send: F) -> Vec<RelayResult<T>>
+    where
+        F: Fn(&'a dyn Relay) -> Fut,
+        Fut: Future<Output = Result<T>> + 'a,
+    {
+        join_all(self.relays.iter().map(|entry| {
+            let timeout = entry.timeout.unwrap_or(self.timeout);
+            let request = send(entry.relay.as_ref());
+            async move {
+                let start = Instant::now();
+                let result = match tokio::time::timeout(timeout, request).await {
+                    Ok(result) => result,
+                    Err(_) => Err(Error::Timeout(timeout)),
+                };
+                RelayResult {
+                    relay: entry.relay.name().to_string(),
+                    result,
+                    latency: start.elapsed(),
+                }
+            }
+        }))
+        .await
+    }
+}
+
+impl Default for MultiRelayExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Logs the results of a fan-out, failing if no relay accepted the action.
+fn report<T: std::fmt::Debug>(kind: &str, results: Vec<RelayResult<T>>) -> Result<()> {
+    let mut accepted = 0;
+    for result in &results {
+        match &result.result {
+            Ok(response) => {
+                accepted += 1;
+                info!(
+                    "{} accepted by {} in {:?}: {:?}",
+                    kind, result.relay, result.latency, response
+                );
+            }
+            Err(e) => warn!(
+                "{} rejected by {} in {:?}: {}",
+                kind, result.relay, result.latency, e
+            ),
+        }
+    }
+    if accepted == 0 {
+        return Err(anyhow::anyhow!(
+            "{} was not accepted by any of {} relays",
+            kind,
+            results.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Executor<SubmitBundle> for MultiRelayExecutor {
+    /// Send a bundle to every relay.
+    async fn execute(&self, action: SubmitBundle) -> Result<()> {
+        report("bundle", self.send_bundle(&action).await?)
+    }
+}
+
+#[async_trait]
+impl Executor<SubmitPrivateTx> for MultiRelayExecutor {
+    /// Send a private transaction to every relay.
+    async fn execute(&self, action: SubmitPrivateTx) -> Result<()> {
+        report("private transaction", self.send_private_tx(&action).await?)
+    }
+}
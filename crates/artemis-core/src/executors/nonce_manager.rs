@@ -0,0 +1,143 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::error::Result;
+use alloy::{
+    network::AnyNetwork,
+    primitives::Address,
+    providers::{DynProvider, Provider},
+    rpc::types::{serde_helpers::WithOtherFields, TransactionRequest},
+};
+use tokio::sync::Mutex;
+
+/// How many of the most recent nonces of each sender the sent transactions
+/// are remembered for.
+const SENT_HISTORY: u64 = 256;
+
+/// The next nonce of a sender, if known, behind its own lock so that fetching
+/// it for one sender does not hold up the others.
+type NextNonce = Arc<Mutex<Option<u64>>>;
+
+/// The last transaction sent with each sender and nonce.
+type SentTxs = HashMap<(Address, u64), WithOtherFields<TransactionRequest>>;
+
+/// Hands out nonces for transactions sent by one or more accounts, so that
+/// transactions from the same sender submitted close together don't collide.
+///
+/// The next nonce of a sender is fetched from the node's pending transaction
+/// count the first time it is needed, then tracked locally. Call
+/// [resync](NonceManager::resync) when the node rejects a nonce with "nonce
+/// too low", to start over from the node's view, and
+/// [release](NonceManager::release) a nonce that was reserved but not used.
+///
+/// The manager also remembers the last transaction sent with each recent
+/// nonce, so that pending transactions can be replaced or cancelled.
+///
+/// The manager is cheap to clone, and clones share the same nonces, so it can
+/// be shared between executors submitting for the same accounts.
+#[derive(Debug, Clone)]
+pub struct NonceManager {
+    client: Arc<DynProvider<AnyNetwork>>,
+    nonces: Arc<std::sync::Mutex<HashMap<Address, NextNonce>>>,
+    sent: Arc<std::sync::Mutex<SentTxs>>,
+}
+
+impl NonceManager {
+    pub fn new(client: Arc<DynProvider<AnyNetwork>>) -> Self {
+        Self {
+            client,
+            nonces: Default::default(),
+            sent: Default::default(),
+        }
+    }
+
+    /// Reserves and returns the next nonce of the sender.
+    pub async fn next_nonce(&self, sender: Address) -> Result<u64> {
+        let next = self.next(sender);
+        let mut next = next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => self.pending_count(sender).await?,
+        };
+        *next = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Returns the nonce that will be handed out next for the sender, without
+    /// reserving it.
+    pub async fn peek_nonce(&self, sender: Address) -> Result<u64> {
+        let next = self.next(sender);
+        let mut next = next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => self.pending_count(sender).await?,
+        };
+        *next = Some(nonce);
+        Ok(nonce)
+    }
+
+    /// Discards the locally tracked nonce of the sender and refetches it from
+    /// the node, returning the next nonce that will be handed out.
+    pub async fn resync(&self, sender: Address) -> Result<u64> {
+        let next = self.next(sender);
+        let mut next = next.lock().await;
+        let nonce = self.pending_count(sender).await?;
+        *next = Some(nonce);
+        Ok(nonce)
+    }
+
+    /// Gives back a reserved nonce that was not used, if it is still the last
+    /// nonce handed out for the sender, returning whether it was released.
+    ///
+    /// Otherwise later nonces are in use, and the nonce is left as a gap,
+    /// which the node reports once a later transaction is stuck behind it.
+    pub async fn release(&self, sender: Address, nonce: u64) -> bool {
+        let next = self.next(sender);
+        let mut next = next.lock().await;
+        if *next != Some(nonce + 1) {
+            return false;
+        }
+        *next = Some(nonce);
+        true
+    }
+
+    /// Forgets the locally tracked nonce of the sender, so that it is fetched
+    /// from the node the next time it is needed.
+    pub async fn reset(&self, sender: Address) {
+        *self.next(sender).lock().await = None;
+    }
+
+    /// Records a transaction sent with the given sender and nonce, replacing
+    /// any transaction previously recorded for them.
+    pub fn record_sent(
+        &self,
+        sender: Address,
+        nonce: u64,
+        tx: WithOtherFields<TransactionRequest>,
+    ) {
+        let mut sent = self.sent.lock().unwrap();
+        sent.retain(|(from, sent_nonce), _| {
+            *from != sender || sent_nonce.saturating_add(SENT_HISTORY) > nonce
+        });
+        sent.insert((sender, nonce), tx);
+    }
+
+    /// Returns the last transaction recorded for the given sender and nonce.
+    pub fn sent(&self, sender: Address, nonce: u64) -> Option<WithOtherFields<TransactionRequest>> {
+        self.sent.lock().unwrap().get(&(sender, nonce)).cloned()
+    }
+
+    /// Returns the slot holding the next nonce of the sender, creating it if
+    /// the sender is new.
+    fn next(&self, sender: Address) -> NextNonce {
+        self.nonces
+            .lock()
+            .unwrap()
+            .entry(sender)
+            .or_default()
+            .clone()
+    }
+
+    async fn pending_count(&self, sender: Address) -> Result<u64> {
+        Ok(self.client.get_transaction_count(sender).pending().await?)
+    }
+}
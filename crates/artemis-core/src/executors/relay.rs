@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+
+use crate::error::{Error, Result};
+use alloy::{
+    hex,
+    primitives::{keccak256, TxHash},
+    rpc::{
+        json_rpc::{ErrorPayload, Id, Request, RpcError, RpcRecv, RpcSend},
+        types::mev::{EthBundleHash, EthSendBundle, PrivateTransactionRequest},
+    },
+    signers::{local::PrivateKeySigner, Signer},
+    transports::TransportErrorKind,
+};
+use async_trait::async_trait;
+use reqwest::Url;
+use serde_json::Value;
+
+/// A relay or builder endpoint that bundles and private transactions can be
+/// sent to.
+///
+/// [RelayClient] implements the standard Flashbots API. Endpoints that deviate
+/// from it, e.g. by rejecting some bundle fields or naming methods differently,
+/// can be supported by an adapter that wraps a [RelayClient] and adjusts the
+/// requests.
+#[async_trait]
+pub trait Relay: Send + Sync {
+    /// The name of the relay, used in logs and results.
+    fn name(&self) -> &str;
+
+    /// Sends a bundle with `eth_sendBundle`.
+    async fn send_bundle(&self, bundle: EthSendBundle) -> Result<EthBundleHash>;
+
+    /// Sends a private transaction with `eth_sendPrivateTransaction`.
+    async fn send_private_transaction(&self, tx: PrivateTransactionRequest) -> Result<TxHash>;
+}
+
+/// A JSON-RPC client for a Flashbots-style relay or builder endpoint.
+///
+/// Every request is authenticated with an `X-Flashbots-Signature` header,
+/// which signs the hash of the request body with the client's auth key. The
+/// auth key only identifies the searcher to the relay and should not hold
+/// funds.
+#[derive(Debug, Clone)]
+pub struct RelayClient {
+    name: String,
+    http: reqwest::Client,
+    url: Url,
+    auth_signer: PrivateKeySigner,
+}
+
+impl RelayClient {
+    pub fn new(url: Url, auth_signer: PrivateKeySigner) -> Self {
+        Self {
+            name: url.host_str().unwrap_or_default().to_string(),
+            http: reqwest::Client::new(),
+            url,
+            auth_signer,
+        }
+    }
+
+    /// Sets the name of the relay. Defaults to the host of its URL.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns the URL of the relay.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Sends a signed JSON-RPC request to the relay.
+    ///
+    /// Errors returned by the relay are classified like node errors, see
+    /// [Error::from_submission].
+    pub async fn request<P, R>(&self, method: impl Into<Cow<'static, str>>, params: P) -> Result<R>
+    where
+        P: RpcSend,
+        R: RpcRecv,
+    {
+        let request = Request::new(method, Id::Number(1), params);
+        let body = serde_json::to_vec(&request).map_err(TransportErrorKind::custom)?;
+        let signature = self
+            .auth_signer
+            .sign_message(format!("{:?}", keccak256(&body)).as_bytes())
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to sign relay request: {}", e))?;
+
+        let response = self
+            .http
+            .post(self.url.clone())
+            .header("Content-Type", "application/json")
+            .header(
+                "X-Flashbots-Signature",
+                format!(
+                    "{:?}:{}",
+                    self.auth_signer.address(),
+                    hex::encode_prefixed(signature.as_bytes())
+                ),
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(TransportErrorKind::custom)?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(TransportErrorKind::custom)?;
+
+        // Relays answer some errors with a non-2xx status and a JSON-RPC body.
+        let mut response: Value = serde_json::from_slice(&body).map_err(|_| {
+            TransportErrorKind::custom_str(&format!(
+                "relay responded with status {}: {}",
+                status,
+                String::from_utf8_lossy(&body)
+            ))
+        })?;
+        if let Some(error) = response.get_mut("error").map(Value::take) {
+            let payload: ErrorPayload =
+                serde_json::from_value(error).map_err(TransportErrorKind::custom)?;
+            return Err(Error::from_submission(RpcError::ErrorResp(payload)));
+        }
+        let result = response
+            .get_mut("result")
+            .map(Value::take)
+            .unwrap_or_default();
+        Ok(serde_json::from_value(result).map_err(TransportErrorKind::custom)?)
+    }
+}
+
+#[async_trait]
+impl Relay for RelayClient {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send_bundle(&self, bundle: EthSendBundle) -> Result<EthBundleHash> {
+        self.request("eth_sendBundle", (bundle,)).await
+    }
+
+    async fn send_private_transaction(&self, tx: PrivateTransactionRequest) -> Result<TxHash> {
+        self.request("eth_sendPrivateTransaction", (tx,)).await
+    }
+}
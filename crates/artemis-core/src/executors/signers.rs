@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use crate::error::Result;
+use alloy::{
+    network::EthereumWallet,
+    signers::local::{coins_bip39::English, MnemonicBuilder, PrivateKeySigner},
+};
+use anyhow::Context;
+
+/// Loads a signer from a hex-encoded private key, with or without a `0x`
+/// prefix.
+pub fn from_private_key(key: &str) -> Result<PrivateKeySigner> {
+    Ok(key.trim().parse().context("failed to parse private key")?)
+}
+
+/// Loads a signer from an encrypted JSON keystore file.
+pub fn from_keystore(
+    path: impl AsRef<Path>,
+    password: impl AsRef<[u8]>,
+) -> Result<PrivateKeySigner> {
+    let path = path.as_ref();
+    Ok(PrivateKeySigner::decrypt_keystore(path, password)
+        .with_context(|| format!("failed to decrypt keystore {}", path.display()))?)
+}
+
+/// Derives the signer at `index` on the default Ethereum derivation path,
+/// `m/44'/60'/0'/0/{index}`, from an English BIP-39 mnemonic.
+pub fn from_mnemonic(phrase: &str, index: u32) -> Result<PrivateKeySigner> {
+    Ok(MnemonicBuilder::<English>::default()
+        .phrase(phrase)
+        .index(index)
+        .and_then(|builder| builder.build())
+        .with_context(|| format!("failed to derive signer {} from mnemonic", index))?)
+}
+
+/// Returns a wallet holding all the signers. Transactions are signed by the
+/// signer matching their `from`, and the first signer is used for
+/// transactions without one.
+pub fn wallet(signers: impl IntoIterator<Item = PrivateKeySigner>) -> Result<EthereumWallet> {
+    let mut signers = signers.into_iter();
+    let mut wallet = EthereumWallet::from(
+        signers
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("a wallet needs at least one signer"))?,
+    );
+    for signer in signers {
+        wallet.register_signer(signer);
+    }
+    Ok(wallet)
+}
@@ -0,0 +1,209 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::error::{Error, Rejection, Result};
+use alloy::{
+    eips::BlockNumberOrTag,
+    network::{AnyNetwork, TransactionBuilder},
+    primitives::{Address, Bytes, U256},
+    providers::{ext::DebugApi, DynProvider, Provider},
+    rpc::types::{
+        serde_helpers::WithOtherFields,
+        simulate::{SimBlock, SimulatePayload, SimulatedBlock},
+        trace::geth::{
+            CallConfig, CallFrame, GethDebugTracingCallOptions, GethDebugTracingOptions,
+        },
+        TransactionRequest,
+    },
+    serde::OtherFields,
+    sol,
+    sol_types::SolCall,
+    transports::TransportError,
+};
+
+sol! {
+    function balanceOf(address owner) external view returns (uint256);
+}
+
+/// Pre-flight checks run against the pending block before a transaction is
+/// sent, so that transactions that would revert are never broadcast.
+///
+/// The transaction is executed with `eth_call`, or traced with
+/// `debug_traceCall` if enabled and supported by the node, and rejected with
+/// [Rejection::Reverted] if it reverts. A [profit check](Simulation::with_profit_check)
+/// additionally executes it with `eth_simulateV1` between two balance queries
+/// of a token, and rejects it with [Rejection::InsufficientProfit] if the
+/// balance does not grow enough.
+#[derive(Debug, Clone, Default)]
+pub struct Simulation {
+    trace: bool,
+    profit_check: Option<ProfitCheck>,
+    /// Set once the node answered that it does not support `debug_traceCall`.
+    trace_unsupported: Arc<AtomicBool>,
+}
+
+/// A minimum increase of a token balance expected from a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfitCheck {
+    /// The ERC-20 token the profit is made in.
+    pub token: Address,
+
+    /// The account receiving the profit.
+    pub holder: Address,
+
+    /// The minimum increase of the holder's balance.
+    pub min_delta: U256,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Traces the transaction with `debug_traceCall`, which also reports the
+    /// revert reason. Falls back to `eth_call` on nodes without the debug API.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = true;
+        self
+    }
+
+    /// Requires the transaction to increase the token balance of the holder by
+    /// at least `min_delta`.
+    pub fn with_profit_check(mut self, token: Address, holder: Address, min_delta: U256) -> Self {
+        self.profit_check = Some(ProfitCheck {
+            token,
+            holder,
+            min_delta,
+        });
+        self
+    }
+
+    /// Simulates the transaction, failing with [Error::Rejected] if it would
+    /// revert or not make the expected profit.
+    pub async fn check(
+        &self,
+        client: &DynProvider<AnyNetwork>,
+        tx: &WithOtherFields<TransactionRequest>,
+    ) -> Result<()> {
+        if !(self.trace && self.trace_call(client, tx).await?) {
+            client
+                .call(tx)
+                .block(BlockNumberOrTag::Pending.into())
+                .await
+                .map_err(Error::from_submission)?;
+        }
+        if let Some(profit_check) = &self.profit_check {
+            self.check_profit(client, tx, profit_check).await?;
+        }
+        Ok(())
+    }
+
+    /// Traces the transaction, returning false if the node cannot trace it.
+    async fn trace_call(
+        &self,
+        client: &DynProvider<AnyNetwork>,
+        tx: &WithOtherFields<TransactionRequest>,
+    ) -> Result<bool> {
+        if self.trace_unsupported.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        let options = GethDebugTracingCallOptions::new(GethDebugTracingOptions::call_tracer(
+            CallConfig::default(),
+        ));
+        let frame = match client
+            .debug_trace_call_as::<CallFrame>(
+                tx.inner.clone(),
+                BlockNumberOrTag::Pending.into(),
+                options,
+            )
+            .await
+        {
+            Ok(frame) => frame,
+            Err(e) if is_unsupported(&e) => {
+                self.trace_unsupported.store(true, Ordering::Relaxed);
+                return Ok(false);
+            }
+            Err(e) => return Err(Error::from_submission(e)),
+        };
+        match frame.error {
+            Some(error) => Err(Rejection::Reverted(frame.revert_reason.unwrap_or(error)).into()),
+            None => Ok(true),
+        }
+    }
+
+    async fn check_profit(
+        &self,
+        client: &DynProvider<AnyNetwork>,
+        tx: &WithOtherFields<TransactionRequest>,
+        profit_check: &ProfitCheck,
+    ) -> Result<()> {
+        let balance_call = TransactionRequest::default()
+            .with_to(profit_check.token)
+            .with_input(
+                balanceOfCall {
+                    owner: profit_check.holder,
+                }
+                .abi_encode(),
+            );
+        let payload = SimulatePayload::default().extend(
+            SimBlock::default()
+                .call(balance_call.clone())
+                .call(tx.inner.clone())
+                .call(balance_call),
+        );
+        let blocks: Vec<SimulatedBlock<OtherFields>> = client
+            .raw_request(
+                "eth_simulateV1".into(),
+                (payload, BlockNumberOrTag::Pending),
+            )
+            .await
+            .map_err(Error::from_submission)?;
+
+        let calls = match blocks.first() {
+            Some(block) if block.calls.len() == 3 => &block.calls,
+            _ => return Err(anyhow::anyhow!("unexpected eth_simulateV1 response").into()),
+        };
+        if !calls[1].status {
+            let reason = calls[1]
+                .error
+                .as_ref()
+                .map(|error| error.message.clone())
+                .unwrap_or_default();
+            return Err(Rejection::Reverted(reason).into());
+        }
+        let before = decode_balance(&calls[0].return_data)?;
+        let after = decode_balance(&calls[2].return_data)?;
+        if after
+            .checked_sub(before)
+            .is_none_or(|delta| delta < profit_check.min_delta)
+        {
+            return Err(Rejection::InsufficientProfit {
+                before,
+                after,
+                min_delta: profit_check.min_delta,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+fn decode_balance(data: &Bytes) -> Result<U256> {
+    balanceOfCall::abi_decode_returns(data, true)
+        .map(|balance| balance._0)
+        .map_err(|e| anyhow::anyhow!("invalid balanceOf result: {}", e).into())
+}
+
+/// Returns true if the error means the node does not support the method.
+fn is_unsupported(err: &TransportError) -> bool {
+    err.as_error_resp().is_some_and(|payload| {
+        let message = payload.message.to_lowercase();
+        payload.code == -32601
+            || message.contains("not found")
+            || message.contains("does not exist")
+            || message.contains("not available")
+            || message.contains("not supported")
+    })
+}
@@ -0,0 +1,168 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::collectors::tx_outcome_collector::TxOutcomeCollector;
+use crate::error::Result;
+use alloy::{
+    network::{AnyNetwork, AnyTransactionReceipt, ReceiptResponse},
+    primitives::{Address, TxHash},
+    providers::{DynProvider, Provider},
+};
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// Watches submitted transactions until they are included, replaced or time
+/// out, and publishes their [outcome](TxOutcome) to its
+/// [TxOutcomeCollector]s.
+///
+/// Add the collector returned by [collector](TxTracker::collector) to the
+/// engine to feed the outcomes back to strategies. Outcomes published while no
+/// collector is running are dropped.
+#[derive(Debug, Clone)]
+pub struct TxTracker {
+    client: Arc<DynProvider<AnyNetwork>>,
+    outcomes: broadcast::Sender<TxOutcome>,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+/// The outcome of a submitted transaction.
+#[derive(Debug, Clone)]
+pub struct TxOutcome {
+    /// The hash of the submitted transaction.
+    pub hash: TxHash,
+
+    /// The sender of the transaction, if known when it was submitted.
+    pub sender: Option<Address>,
+
+    /// The nonce of the transaction, if known when it was submitted.
+    pub nonce: Option<u64>,
+
+    /// What happened to the transaction.
+    pub status: TxStatus,
+}
+
+/// What happened to a submitted transaction.
+#[derive(Debug, Clone)]
+pub enum TxStatus {
+    /// The transaction was included in a block.
+    Included {
+        /// The block the transaction was included in.
+        block_number: u64,
+        /// The gas used by the transaction.
+        gas_used: u64,
+        /// The price paid per gas, including the base fee.
+        effective_gas_price: u128,
+        /// Whether the transaction succeeded or reverted.
+        success: bool,
+        /// The full receipt of the transaction.
+        receipt: Box<AnyTransactionReceipt>,
+    },
+
+    /// Another transaction with the same sender and nonce was included
+    /// instead.
+    Replaced,
+
+    /// The transaction was still pending when the tracker gave up on it.
+    TimedOut,
+}
+
+impl TxTracker {
+    pub fn new(client: Arc<DynProvider<AnyNetwork>>) -> Self {
+        let (outcomes, _) = broadcast::channel(512);
+        Self {
+            client,
+            outcomes,
+            timeout: Duration::from_secs(120),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets how long a transaction is watched before it is reported as timed
+    /// out. Defaults to 2 minutes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how often the node is polled for the receipt. Defaults to 1 second.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Returns a collector emitting the outcome of every tracked transaction.
+    pub fn collector(&self) -> TxOutcomeCollector {
+        TxOutcomeCollector::new(self.outcomes.clone())
+    }
+
+    /// Watches the transaction in the background and publishes its outcome.
+    ///
+    /// With a sender and nonce, the transaction is reported as replaced as
+    /// soon as the nonce is used by another transaction. Failed polls are
+    /// logged and retried, and an outcome is always published, at the latest
+    /// once the timeout expires.
+    pub fn track(&self, hash: TxHash, sender: Option<Address>, nonce: Option<u64>) {
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            let status = tracker.watch(hash, sender.zip(nonce)).await;
+            debug!("transaction {} outcome: {:?}", hash, status);
+            let _ = tracker.outcomes.send(TxOutcome {
+                hash,
+                sender,
+                nonce,
+                status,
+            });
+        });
+    }
+
+    async fn watch(&self, hash: TxHash, sender_nonce: Option<(Address, u64)>) -> TxStatus {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match self.poll(hash, sender_nonce).await {
+                Ok(Some(status)) => return status,
+                Ok(None) => {}
+                Err(e) => warn!("error polling transaction {}, retrying: {}", hash, e),
+            }
+            if Instant::now() + self.poll_interval > deadline {
+                return TxStatus::TimedOut;
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Queries the node once, returning the outcome of the transaction if it
+    /// is known.
+    async fn poll(
+        &self,
+        hash: TxHash,
+        sender_nonce: Option<(Address, u64)>,
+    ) -> Result<Option<TxStatus>> {
+        if let Some(receipt) = self.client.get_transaction_receipt(hash).await? {
+            return Ok(Some(included(receipt)));
+        }
+        if let Some((sender, nonce)) = sender_nonce {
+            let mined = self.client.get_transaction_count(sender).latest().await?;
+            if mined > nonce {
+                // The receipt may have landed since it was last queried.
+                return Ok(Some(
+                    match self.client.get_transaction_receipt(hash).await? {
+                        Some(receipt) => included(receipt),
+                        None => TxStatus::Replaced,
+                    },
+                ));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn included(receipt: AnyTransactionReceipt) -> TxStatus {
+    TxStatus::Included {
+        block_number: receipt.block_number().unwrap_or_default(),
+        gas_used: receipt.gas_used(),
+        effective_gas_price: receipt.effective_gas_price(),
+        success: receipt.status(),
+        receipt: Box::new(receipt),
+    }
+}
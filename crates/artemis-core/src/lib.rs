@@ -10,27 +10,49 @@
 //! library is made up of three main components:
 //!
 //! 1. [Collectors](types::Collector): *Collectors* take in external events (such as pending txs,
-//!  new blocks, marketplace orders, etc. ) and turn them into an internal
-//! *event* representation.
+//!    new blocks, marketplace orders, etc. ) and turn them into an internal
+//!    *event* representation.
 //!
 //! 2. [Strategies](types::Strategy): *Strategies* contain the core logic required for each MEV
-//! opportunity. They take in *events* as inputs, and compute whether any
-//! opportunities are available (for example, a strategy might listen to a stream
-//! of marketplace orders to see if there are any cross-exchange arbs). *Strategies*
-//! produce *actions*.
+//!    opportunity. They take in *events* as inputs, and compute whether any
+//!    opportunities are available (for example, a strategy might listen to a stream
+//!    of marketplace orders to see if there are any cross-exchange arbs). *Strategies*
+//!    produce *actions*.
 //!
 //! 3. [Executors](types::Executor): *Executors* process *actions*, and are responsible for executing
-//! them in different domains (for example, submitting txs, posting off-chain orders, etc.).
+//!    them in different domains (for example, submitting txs, posting off-chain orders, etc.).
 //!
 //! These components are tied together by the [Engine](engine::Engine), which is responsible for
 //! orchestrating the flow of data between them.
 
+// Dev-dependencies are only used by the integration tests.
+#[cfg(test)]
+use {alloy_node_bindings as _, tracing_subscriber as _};
+
 /// This module contains [collector](types::Collector) implementations.
 pub mod collectors;
+/// This module contains the per-component configuration returned when adding
+/// components to the [Engine](engine::Engine).
+pub mod config;
 /// This module contains the [Engine](engine::Engine) struct, which is responsible
 /// for orchestrating data flows between components
 pub mod engine;
+mod envelope;
+/// This module contains the [Error](error::Error) type returned across Artemis.
+pub mod error;
 /// This module contains [executor](types::Executor) implementations.
 pub mod executors;
+/// This module contains the Prometheus metrics recorded with the `metrics` feature.
+pub mod metrics;
+/// This module contains the [ShutdownHandle](shutdown::ShutdownHandle) used to
+/// gracefully stop a running engine.
+pub mod shutdown;
+/// This module contains the [StatusHandle](status::StatusHandle) reporting the
+/// health of a running engine's components.
+pub mod status;
+/// This module contains helpers to compose [strategies](types::Strategy).
+pub mod strategies;
 /// This module contains the core type definitions for Artemis.
 pub mod types;
+
+pub use error::Error;
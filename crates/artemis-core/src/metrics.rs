@@ -0,0 +1,314 @@
+//! Prometheus metrics for the event and action pipeline.
+//!
+//! Metrics are only recorded when the `metrics` feature is enabled and an
+//! [EngineMetrics] is passed to [Engine::with_metrics](crate::engine::Engine::with_metrics).
+//! Without the feature the engine records nothing and this module only
+//! contains no-op internals.
+
+use std::time::Instant;
+
+#[cfg(feature = "metrics")]
+pub use self::prometheus_metrics::{serve, EngineMetrics};
+
+/// The metrics an engine records into, if any.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Metrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<EngineMetrics>,
+}
+
+impl Metrics {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn new(metrics: EngineMetrics) -> Self {
+        Self {
+            inner: Some(metrics),
+        }
+    }
+
+    pub(crate) fn collector(&self, name: &str) -> CollectorMetrics {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.inner {
+            return metrics.collector(name);
+        }
+        let _ = name;
+        CollectorMetrics::default()
+    }
+
+    pub(crate) fn strategy(&self, name: &str) -> StrategyMetrics {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.inner {
+            return metrics.strategy(name);
+        }
+        let _ = name;
+        StrategyMetrics::default()
+    }
+
+    pub(crate) fn executor(&self, name: &str) -> ExecutorMetrics {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.inner {
+            return metrics.executor(name);
+        }
+        let _ = name;
+        ExecutorMetrics::default()
+    }
+}
+
+/// Metrics recorded for a single collector.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CollectorMetrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<(prometheus::IntCounter, prometheus::IntGauge)>,
+}
+
+impl CollectorMetrics {
+    /// Records an emitted event and the resulting depth of the event channel.
+    pub(crate) fn event_emitted(&self, channel_depth: usize) {
+        #[cfg(feature = "metrics")]
+        if let Some((events, depth)) = &self.inner {
+            events.inc();
+            depth.set(channel_depth as i64);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = channel_depth;
+    }
+}
+
+/// Metrics recorded for a single strategy.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StrategyMetrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<(
+        prometheus::IntCounter,
+        prometheus::Histogram,
+        prometheus::IntCounter,
+        prometheus::IntGauge,
+    )>,
+}
+
+impl StrategyMetrics {
+    /// Records a processed event, how long processing took, the number of
+    /// actions emitted and the resulting depth of the action channel.
+    pub(crate) fn event_processed(&self, started: Instant, actions: usize, channel_depth: usize) {
+        #[cfg(feature = "metrics")]
+        if let Some((events, latency, emitted, depth)) = &self.inner {
+            events.inc();
+            latency.observe(started.elapsed().as_secs_f64());
+            emitted.inc_by(actions as u64);
+            depth.set(channel_depth as i64);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = (started, actions, channel_depth);
+    }
+}
+
+/// Metrics recorded for a single executor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExecutorMetrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<(
+        prometheus::IntCounter,
+        prometheus::IntCounter,
+        prometheus::Histogram,
+        prometheus::Histogram,
+    )>,
+}
+
+impl ExecutorMetrics {
+    /// Records the outcome of an execution, how long it took, and how long it
+    /// has been since the originating event was received.
+    pub(crate) fn action_executed(&self, started: Instant, received_at: Instant, success: bool) {
+        #[cfg(feature = "metrics")]
+        if let Some((successes, failures, latency, end_to_end)) = &self.inner {
+            if success {
+                successes.inc();
+            } else {
+                failures.inc();
+            }
+            latency.observe(started.elapsed().as_secs_f64());
+            end_to_end.observe(received_at.elapsed().as_secs_f64());
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = (started, received_at, success);
+    }
+}
+
+#[cfg(feature = "metrics")]
+mod prometheus_metrics {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use prometheus::{
+        Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+        TextEncoder,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tracing::error;
+
+    use super::{CollectorMetrics, ExecutorMetrics, StrategyMetrics};
+
+    /// How long to wait before accepting connections again after an error.
+    const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+    /// The set of Prometheus metrics recorded by an [Engine](crate::engine::Engine).
+    ///
+    /// All metrics are labelled with the name of the component that recorded them.
+    #[derive(Debug, Clone)]
+    pub struct EngineMetrics {
+        collector_events: IntCounterVec,
+        strategy_events: IntCounterVec,
+        strategy_latency: HistogramVec,
+        strategy_actions: IntCounterVec,
+        executor_actions: IntCounterVec,
+        executor_latency: HistogramVec,
+        end_to_end_latency: HistogramVec,
+        channel_depth: IntGaugeVec,
+    }
+
+    impl EngineMetrics {
+        /// Creates the engine metrics and registers them with the given registry.
+        pub fn new(registry: &Registry) -> prometheus::Result<Self> {
+            let metrics = Self {
+                collector_events: IntCounterVec::new(
+                    Opts::new(
+                        "artemis_collector_events_total",
+                        "Events emitted by each collector",
+                    ),
+                    &["collector"],
+                )?,
+                strategy_events: IntCounterVec::new(
+                    Opts::new(
+                        "artemis_strategy_events_total",
+                        "Events processed by each strategy",
+                    ),
+                    &["strategy"],
+                )?,
+                strategy_latency: HistogramVec::new(
+                    HistogramOpts::new(
+                        "artemis_strategy_process_event_seconds",
+                        "Time spent by each strategy processing an event",
+                    ),
+                    &["strategy"],
+                )?,
+                strategy_actions: IntCounterVec::new(
+                    Opts::new(
+                        "artemis_strategy_actions_total",
+                        "Actions emitted by each strategy",
+                    ),
+                    &["strategy"],
+                )?,
+                executor_actions: IntCounterVec::new(
+                    Opts::new(
+                        "artemis_executor_actions_total",
+                        "Actions executed by each executor, by result",
+                    ),
+                    &["executor", "result"],
+                )?,
+                executor_latency: HistogramVec::new(
+                    HistogramOpts::new(
+                        "artemis_executor_execute_seconds",
+                        "Time spent by each executor executing an action",
+                    ),
+                    &["executor"],
+                )?,
+                end_to_end_latency: HistogramVec::new(
+                    HistogramOpts::new(
+                        "artemis_event_to_action_seconds",
+                        "Time from receiving an event to executing the resulting action",
+                    ),
+                    &["executor"],
+                )?,
+                channel_depth: IntGaugeVec::new(
+                    Opts::new(
+                        "artemis_channel_depth",
+                        "Number of messages queued in the event and action channels",
+                    ),
+                    &["channel"],
+                )?,
+            };
+            registry.register(Box::new(metrics.collector_events.clone()))?;
+            registry.register(Box::new(metrics.strategy_events.clone()))?;
+            registry.register(Box::new(metrics.strategy_latency.clone()))?;
+            registry.register(Box::new(metrics.strategy_actions.clone()))?;
+            registry.register(Box::new(metrics.executor_actions.clone()))?;
+            registry.register(Box::new(metrics.executor_latency.clone()))?;
+            registry.register(Box::new(metrics.end_to_end_latency.clone()))?;
+            registry.register(Box::new(metrics.channel_depth.clone()))?;
+            Ok(metrics)
+        }
+
+        pub(crate) fn collector(&self, name: &str) -> CollectorMetrics {
+            CollectorMetrics {
+                inner: Some((
+                    self.collector_events.with_label_values(&[name]),
+                    self.channel_depth.with_label_values(&["event"]),
+                )),
+            }
+        }
+
+        pub(crate) fn strategy(&self, name: &str) -> StrategyMetrics {
+            StrategyMetrics {
+                inner: Some((
+                    self.strategy_events.with_label_values(&[name]),
+                    self.strategy_latency.with_label_values(&[name]),
+                    self.strategy_actions.with_label_values(&[name]),
+                    self.channel_depth.with_label_values(&["action"]),
+                )),
+            }
+        }
+
+        pub(crate) fn executor(&self, name: &str) -> ExecutorMetrics {
+            ExecutorMetrics {
+                inner: Some((
+                    self.executor_actions.with_label_values(&[name, "success"]),
+                    self.executor_actions.with_label_values(&[name, "failure"]),
+                    self.executor_latency.with_label_values(&[name]),
+                    self.end_to_end_latency.with_label_values(&[name]),
+                )),
+            }
+        }
+    }
+
+    /// Serves the metrics of the registry in the Prometheus text format to any
+    /// HTTP request on the given address, until the returned future is dropped.
+    ///
+    /// Only fails if the address cannot be bound. Errors accepting a
+    /// connection, e.g. when running out of file descriptors, are logged and
+    /// the server keeps listening.
+    pub async fn serve(registry: Registry, addr: SocketAddr) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        loop {
+            let mut stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    error!("error accepting metrics connection: {}", e);
+                    tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                    continue;
+                }
+            };
+            let registry = registry.clone();
+            tokio::spawn(async move {
+                // The request itself is irrelevant, every path returns the metrics.
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+
+                let mut body = vec![];
+                let encoder = TextEncoder::new();
+                if let Err(e) = encoder.encode(&registry.gather(), &mut body) {
+                    error!("error encoding metrics: {}", e);
+                    return;
+                }
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    encoder.format_type(),
+                    body.len()
+                );
+                if let Err(e) = stream.write_all(header.as_bytes()).await {
+                    error!("error writing metrics response: {}", e);
+                    return;
+                }
+                let _ = stream.write_all(&body).await;
+            });
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// A handle used to request a graceful shutdown of a running [Engine](crate::engine::Engine).
+///
+/// Shutting down stops all collectors first, then lets strategies process the
+/// events that are already buffered, and finally waits for executors to finish
+/// the pending actions. Components still running when the engine's shutdown
+/// timeout expires are aborted.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    token: CancellationToken,
+    summary: watch::Receiver<Option<ShutdownSummary>>,
+}
+
+/// A summary of how the engine stopped, returned once shutdown has completed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// Number of collectors that stopped before the shutdown timeout.
+    pub collectors: usize,
+
+    /// Number of strategies that processed all pending events and exited.
+    pub strategies: usize,
+
+    /// Number of executors that executed all pending actions and exited.
+    pub executors: usize,
+
+    /// Number of collector, strategy and executor tasks that were aborted
+    /// because they did not finish before the shutdown timeout.
+    pub aborted: usize,
+
+    /// Number of events processed by strategies after shutdown was requested.
+    pub events_drained: u64,
+
+    /// Number of actions executed after shutdown was requested.
+    pub actions_drained: u64,
+
+    /// Time between the shutdown request and the engine stopping.
+    pub elapsed: Duration,
+}
+
+impl ShutdownHandle {
+    pub(crate) fn new(
+        token: CancellationToken,
+        summary: watch::Receiver<Option<ShutdownSummary>>,
+    ) -> Self {
+        Self { token, summary }
+    }
+
+    /// Requests shutdown without waiting for the engine to stop.
+    pub fn trigger(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns true if shutdown has been requested.
+    pub fn is_triggered(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Requests shutdown and waits for the engine to stop.
+    pub async fn shutdown(self) -> ShutdownSummary {
+        self.trigger();
+        self.wait().await
+    }
+
+    /// Waits for the engine to stop, either because shutdown was requested or
+    /// because all collectors finished on their own. Returns a default summary
+    /// if the engine was dropped without being run.
+    pub async fn wait(mut self) -> ShutdownSummary {
+        match self.summary.wait_for(Option::is_some).await {
+            Ok(summary) => summary.clone().unwrap_or_default(),
+            Err(_) => ShutdownSummary::default(),
+        }
+    }
+}
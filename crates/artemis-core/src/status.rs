@@ -0,0 +1,228 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::watch;
+
+use crate::config::Counter;
+
+/// The kind of component a status refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentKind {
+    Collector,
+    Strategy,
+    Executor,
+}
+
+/// The lifecycle state of a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentState {
+    /// The component is starting, e.g. a collector opening its stream.
+    Starting,
+
+    /// The component is processing messages.
+    Running,
+
+    /// The collector's stream failed or ended and it is waiting to restart.
+    Restarting,
+
+    /// The component exited cleanly.
+    Stopped,
+
+    /// The component exited because of an unrecoverable error.
+    Failed,
+}
+
+/// A point-in-time view of a single component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentStatus {
+    /// The name of the component, as set in its configuration.
+    pub name: String,
+
+    /// The kind of the component.
+    pub kind: ComponentKind,
+
+    /// The current lifecycle state.
+    pub state: ComponentState,
+
+    /// When the component last emitted an event (collectors), processed an
+    /// event (strategies) or executed an action (executors).
+    pub last_event: Option<SystemTime>,
+
+    /// Number of events emitted, events processed or actions executed.
+    pub processed: u64,
+
+    /// Number of errors, such as failed executions or failed restarts.
+    pub errors: u64,
+
+    /// Number of messages missed because the component lagged behind.
+    pub lagged: u64,
+
+    /// Number of times the component was restarted.
+    pub restarts: u64,
+}
+
+/// Live statistics shared between a running component and the [StatusHandle].
+#[derive(Debug)]
+pub(crate) struct ComponentStats {
+    name: String,
+    kind: ComponentKind,
+    state: Mutex<ComponentState>,
+    last_event_ms: AtomicU64,
+    processed: AtomicU64,
+    errors: AtomicU64,
+    lagged: Counter,
+    restarts: Counter,
+    changes: Arc<watch::Sender<()>>,
+}
+
+impl ComponentStats {
+    pub(crate) fn set_state(&self, state: ComponentState) {
+        let mut current = self.state.lock().unwrap();
+        if *current != state {
+            *current = state;
+            self.changes.send_replace(());
+        }
+    }
+
+    /// Marks the component as stopped, unless it already failed.
+    pub(crate) fn stop(&self) {
+        let mut current = self.state.lock().unwrap();
+        if !matches!(*current, ComponentState::Stopped | ComponentState::Failed) {
+            *current = ComponentState::Stopped;
+            self.changes.send_replace(());
+        }
+    }
+
+    pub(crate) fn record_processed(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.last_event_ms
+            .store(now.as_millis() as u64, Ordering::Relaxed);
+        self.processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> ComponentStatus {
+        let last_event_ms = self.last_event_ms.load(Ordering::Relaxed);
+        ComponentStatus {
+            name: self.name.clone(),
+            kind: self.kind,
+            state: *self.state.lock().unwrap(),
+            last_event: (last_event_ms > 0)
+                .then(|| UNIX_EPOCH + Duration::from_millis(last_event_ms)),
+            processed: self.processed.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            lagged: self.lagged.get(),
+            restarts: self.restarts.get(),
+        }
+    }
+}
+
+/// The set of component statistics of an engine, filled in when it runs.
+#[derive(Debug, Clone)]
+pub(crate) struct StatusRegistry {
+    components: Arc<Mutex<Vec<Arc<ComponentStats>>>>,
+    changes: Arc<watch::Sender<()>>,
+}
+
+impl StatusRegistry {
+    pub(crate) fn new() -> Self {
+        let (changes, _) = watch::channel(());
+        Self {
+            components: Default::default(),
+            changes: Arc::new(changes),
+        }
+    }
+
+    /// Registers a component and returns its live statistics.
+    pub(crate) fn register(
+        &self,
+        name: &str,
+        kind: ComponentKind,
+        lagged: Counter,
+        restarts: Counter,
+    ) -> Arc<ComponentStats> {
+        let stats = Arc::new(ComponentStats {
+            name: name.to_string(),
+            kind,
+            state: Mutex::new(ComponentState::Starting),
+            last_event_ms: AtomicU64::new(0),
+            processed: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            lagged,
+            restarts,
+            changes: self.changes.clone(),
+        });
+        self.components.lock().unwrap().push(stats.clone());
+        self.changes.send_replace(());
+        stats
+    }
+
+    /// Marks every component that has not failed as stopped.
+    pub(crate) fn stop_all(&self) {
+        for stats in self.components.lock().unwrap().iter() {
+            stats.stop();
+        }
+    }
+
+    pub(crate) fn handle(&self) -> StatusHandle {
+        StatusHandle {
+            registry: self.clone(),
+            changes: self.changes.subscribe(),
+        }
+    }
+}
+
+/// A handle to poll or subscribe to the status of an engine's components.
+///
+/// Components are registered when the engine starts running, so a handle
+/// taken beforehand reports no components until then.
+#[derive(Debug, Clone)]
+pub struct StatusHandle {
+    registry: StatusRegistry,
+    changes: watch::Receiver<()>,
+}
+
+impl StatusHandle {
+    /// Returns the current status of every component.
+    pub fn snapshot(&self) -> Vec<ComponentStatus> {
+        self.registry
+            .components
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|stats| stats.snapshot())
+            .collect()
+    }
+
+    /// Returns the current status of the component with the given name.
+    pub fn component(&self, name: &str) -> Option<ComponentStatus> {
+        self.registry
+            .components
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|stats| stats.name == name)
+            .map(|stats| stats.snapshot())
+    }
+
+    /// Returns true if the engine is running and every component is running.
+    pub fn is_healthy(&self) -> bool {
+        let status = self.snapshot();
+        !status.is_empty()
+            && status
+                .iter()
+                .all(|component| component.state == ComponentState::Running)
+    }
+
+    /// Waits until a component is registered or changes state.
+    pub async fn changed(&mut self) {
+        // The handle keeps the sender alive, so this never fails.
+        let _ = self.changes.changed().await;
+    }
+}
@@ -0,0 +1,116 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+
+use crate::error::Result;
+use crate::types::Strategy;
+
+/// FilteredStrategy is a wrapper around a [Strategy](Strategy) that only
+/// passes it the events matching a predicate.
+pub struct FilteredStrategy<E, A, F> {
+    strategy: Box<dyn Strategy<E, A>>,
+    f: F,
+}
+
+impl<E, A, F> FilteredStrategy<E, A, F> {
+    pub fn new(strategy: Box<dyn Strategy<E, A>>, f: F) -> Self {
+        Self { strategy, f }
+    }
+}
+
+#[async_trait]
+impl<E, A, F> Strategy<E, A> for FilteredStrategy<E, A, F>
+where
+    E: Send + Sync + 'static,
+    A: Send + Sync + 'static,
+    F: Fn(&E) -> bool + Send + Sync + 'static,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        self.strategy.sync_state().await
+    }
+
+    async fn process_event(&mut self, event: E) -> Vec<A> {
+        if !(self.f)(&event) {
+            return vec![];
+        }
+        self.strategy.process_event(event).await
+    }
+
+    async fn on_lag(&mut self, missed: u64) {
+        self.strategy.on_lag(missed).await
+    }
+}
+
+/// ChainedStrategy feeds the actions of a first [Strategy](Strategy) to a
+/// second one as events, e.g. to pass the actions of a strategy through a
+/// risk filter before they are executed.
+pub struct ChainedStrategy<E, M, A> {
+    first: Box<dyn Strategy<E, M>>,
+    second: Box<dyn Strategy<M, A>>,
+}
+
+impl<E, M, A> ChainedStrategy<E, M, A> {
+    pub fn new(first: Box<dyn Strategy<E, M>>, second: Box<dyn Strategy<M, A>>) -> Self {
+        Self { first, second }
+    }
+}
+
+#[async_trait]
+impl<E, M, A> Strategy<E, A> for ChainedStrategy<E, M, A>
+where
+    E: Send + Sync + 'static,
+    M: Send + Sync + 'static,
+    A: Send + Sync + 'static,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        self.first.sync_state().await?;
+        self.second.sync_state().await
+    }
+
+    async fn process_event(&mut self, event: E) -> Vec<A> {
+        let mut actions = vec![];
+        for intermediate in self.first.process_event(event).await {
+            actions.extend(self.second.process_event(intermediate).await);
+        }
+        actions
+    }
+
+    /// Only the first strategy receives events from the engine, so only it is
+    /// told about missed events.
+    async fn on_lag(&mut self, missed: u64) {
+        self.first.on_lag(missed).await
+    }
+}
+
+/// FnStrategy is a [Strategy](Strategy) built from a closure turning each
+/// event into actions. It has no state to sync, but the closure may keep state
+/// in its captures.
+pub struct FnStrategy<E, A, F> {
+    f: F,
+    _types: PhantomData<fn(E) -> A>,
+}
+
+impl<E, A, F> FnStrategy<E, A, F> {
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            _types: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, A, F> Strategy<E, A> for FnStrategy<E, A, F>
+where
+    E: Send + Sync + 'static,
+    A: Send + Sync + 'static,
+    F: FnMut(E) -> Vec<A> + Send + Sync + 'static,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: E) -> Vec<A> {
+        (self.f)(event)
+    }
+}
@@ -0,0 +1,6 @@
+//! Strategies contain the core logic of each opportunity. Strategies are
+//! written by users, this module contains helpers to build and compose them.
+
+/// This module contains wrappers filtering and chaining strategies, and
+/// building strategies from closures.
+pub mod combinators;
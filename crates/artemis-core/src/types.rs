@@ -1,12 +1,20 @@
-use alloy::rpc::types::Transaction;
-use anyhow::Result;
+use alloy::{
+    network::AnyTxEnvelope,
+    rpc::types::{serde_helpers::WithOtherFields, Log, Transaction},
+};
 use async_trait::async_trait;
 use std::pin::Pin;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
 
 use crate::collectors::block_collector::NewBlock;
-use crate::executors::mempool_executor::SubmitTxToMempool;
+use crate::collectors::block_details_collector::BlockDetails;
+use crate::collectors::reorg_collector::BlockEvent;
+use crate::error::Result;
+use crate::executors::bundle_executor::SubmitBundle;
+use crate::executors::mempool_executor::{CancelTx, SpeedUpTx, SubmitTxToMempool};
+use crate::executors::multi_relay_executor::SubmitPrivateTx;
+use crate::executors::tx_tracker::TxOutcome;
 
 /// A stream of events emitted by a [Collector](Collector).
 pub type CollectorStream<'a, E> = Pin<Box<dyn Stream<Item = E> + Send + 'a>>;
@@ -27,6 +35,10 @@ pub trait Strategy<E, A>: Send + Sync {
 
     /// Process an event, and return an action if needed.
     async fn process_event(&mut self, event: E) -> Vec<A>;
+
+    /// Called when the strategy fell behind the event channel and `missed`
+    /// events were dropped before it could process them. Does nothing by default.
+    async fn on_lag(&mut self, _missed: u64) {}
 }
 
 /// Executor trait, responsible for executing actions returned by strategies.
@@ -63,6 +75,55 @@ where
     }
 }
 
+/// StrategyMap is a wrapper around a [Strategy](Strategy) that maps incoming
+/// events and outgoing actions to different types.
+///
+/// Events mapped to `None` are not passed to the strategy, so a strategy
+/// written for a single event type can be plugged into an engine over
+/// [Events](Events).
+pub struct StrategyMap<E, A, F, G> {
+    strategy: Box<dyn Strategy<E, A>>,
+    map_event: F,
+    map_action: G,
+}
+
+impl<E, A, F, G> StrategyMap<E, A, F, G> {
+    pub fn new(strategy: Box<dyn Strategy<E, A>>, map_event: F, map_action: G) -> Self {
+        Self {
+            strategy,
+            map_event,
+            map_action,
+        }
+    }
+}
+
+#[async_trait]
+impl<E1, E2, A1, A2, F, G> Strategy<E1, A1> for StrategyMap<E2, A2, F, G>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    A1: Send + Sync + 'static,
+    A2: Send + Sync + 'static,
+    F: Fn(E1) -> Option<E2> + Send + Sync + 'static,
+    G: Fn(A2) -> A1 + Send + Sync + 'static,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        self.strategy.sync_state().await
+    }
+
+    async fn process_event(&mut self, event: E1) -> Vec<A1> {
+        let Some(event) = (self.map_event)(event) else {
+            return vec![];
+        };
+        let actions = self.strategy.process_event(event).await;
+        actions.into_iter().map(&self.map_action).collect()
+    }
+
+    async fn on_lag(&mut self, missed: u64) {
+        self.strategy.on_lag(missed).await
+    }
+}
+
 /// ExecutorMap is a wrapper around an [Executor](Executor) that maps incoming
 /// actions to a different type.
 pub struct ExecutorMap<A, F> {
@@ -93,12 +154,89 @@ where
 }
 
 /// Convenience enum containing all the events that can be emitted by collectors.
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Events {
     NewBlock(NewBlock),
-    Transaction(Transaction),
+    BlockDetails(BlockDetails),
+    BlockEvent(BlockEvent),
+    Transaction(WithOtherFields<Transaction<AnyTxEnvelope>>),
+    Log(Log),
+    TxOutcome(TxOutcome),
 }
 
 /// Convenience enum containing all the actions that can be executed by executors.
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Actions {
     SubmitTxToMempool(SubmitTxToMempool),
+    SpeedUpTx(SpeedUpTx),
+    CancelTx(CancelTx),
+    SubmitBundle(SubmitBundle),
+    SubmitPrivateTx(SubmitPrivateTx),
+}
+
+impl Events {
+    /// Wraps a collector of one of the event types, e.g. a
+    /// [BlockCollector](crate::collectors::block_collector::BlockCollector),
+    /// into a collector of [Events].
+    pub fn collector<E>(collector: Box<dyn Collector<E>>) -> CollectorMap<E, fn(E) -> Events>
+    where
+        Events: From<E>,
+    {
+        CollectorMap::new(collector, Events::from)
+    }
+}
+
+impl Actions {
+    /// Wraps an executor of one of the action types, e.g. a
+    /// [MempoolExecutor](crate::executors::mempool_executor::MempoolExecutor),
+    /// into an executor of [Actions] that skips the actions of other types.
+    pub fn executor<A>(executor: Box<dyn Executor<A>>) -> ExecutorMap<A, fn(Actions) -> Option<A>>
+    where
+        A: TryFrom<Actions>,
+    {
+        ExecutorMap::new(executor, |action| A::try_from(action).ok())
+    }
 }
+
+/// Implements the conversions between an enum and the types of its variants.
+macro_rules! impl_variants {
+    ($enum:ident { $($variant:ident($ty:ty)),* $(,)? }) => {
+        $(
+            impl From<$ty> for $enum {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value)
+                }
+            }
+
+            impl TryFrom<$enum> for $ty {
+                type Error = $enum;
+
+                fn try_from(value: $enum) -> std::result::Result<Self, $enum> {
+                    match value {
+                        $enum::$variant(inner) => Ok(inner),
+                        other => Err(other),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_variants!(Events {
+    NewBlock(NewBlock),
+    BlockDetails(BlockDetails),
+    BlockEvent(BlockEvent),
+    Transaction(WithOtherFields<Transaction<AnyTxEnvelope>>),
+    Log(Log),
+    TxOutcome(TxOutcome),
+});
+
+impl_variants!(Actions {
+    SubmitTxToMempool(SubmitTxToMempool),
+    SpeedUpTx(SpeedUpTx),
+    CancelTx(CancelTx),
+    SubmitBundle(SubmitBundle),
+    SubmitPrivateTx(SubmitPrivateTx),
+});
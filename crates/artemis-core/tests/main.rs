@@ -1,20 +1,73 @@
 use alloy::{
-    consensus::Transaction,
-    eips::{BlockId, BlockNumberOrTag},
-    network::{AnyNetwork, TransactionBuilder},
-    primitives::U256,
+    consensus::{Transaction, TxEnvelope},
+    eips::{
+        eip2718::{Decodable2718, Encodable2718},
+        BlockId, BlockNumberOrTag,
+    },
+    network::{
+        AnyNetwork, AnyRpcHeader, Ethereum, EthereumWallet, NetworkWallet, TransactionBuilder,
+    },
+    primitives::{keccak256, Address, BlockHash, Bytes, PrimitiveSignature, TxHash, U128, U256},
     providers::{DynProvider, Provider, ProviderBuilder, WsConnect},
     rpc::types::{serde_helpers::WithOtherFields, BlockTransactionsKind, TransactionRequest},
+    signers::local::PrivateKeySigner,
+};
+use alloy::{
+    rpc::json_rpc::{ErrorPayload, RpcError},
+    rpc::types::mev::{EthBundleHash, EthSendBundle, PrivateTransactionRequest},
+    transports::TransportError,
 };
 use alloy_node_bindings::{Anvil, AnvilInstance};
 use artemis_core::{
-    collectors::{block_collector::BlockCollector, mempool_collector::MempoolCollector},
-    executors::mempool_executor::{MempoolExecutor, SubmitTxToMempool},
-    types::{Collector, Executor},
+    collectors::{
+        block_collector::{BlockCollector, NewBlock},
+        block_details_collector::BlockDetailsCollector,
+        combinators::{
+            BatchCollector, DebounceCollector, DedupCollector, FilterCollector, FilterMapCollector,
+            MergeCollector, ThrottleCollector,
+        },
+        mempool_collector::MempoolCollector,
+        reorg_collector::{BlockEvent, ReorgTracker},
+    },
+    config::{LagPolicy, RestartPolicy},
+    engine::Engine,
+    error::{BidError, Error, Rejection, Result},
+    executors::{
+        bundle_executor::{BundleExecutor, BundleTx, SubmitBundle},
+        combinators::{DedupExecutor, RateLimitedExecutor, RetryExecutor, TimeoutExecutor},
+        dry_run::DryRun,
+        mempool_executor::{
+            CancelTx, FeeCaps, GasBidInfo, GasPricing, MempoolExecutor, SpeedUpTx,
+            SubmitTxToMempool,
+        },
+        multi_relay_executor::{MultiRelayExecutor, SubmitPrivateTx},
+        nonce_manager::NonceManager,
+        relay::{Relay, RelayClient},
+        signers,
+        simulation::Simulation,
+        tx_tracker::{TxOutcome, TxStatus, TxTracker},
+    },
+    status::{ComponentKind, ComponentState},
+    strategies::combinators::{ChainedStrategy, FilteredStrategy, FnStrategy},
+    types::{
+        Actions, Collector, CollectorMap, CollectorStream, Events, Executor, Strategy, StrategyMap,
+    },
 };
+use async_trait::async_trait;
 
 use futures::StreamExt;
-use std::{sync::Arc, time::Duration};
+use serde_json::{json, Value};
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
 
 /// Spawns Anvil and instantiates an Http provider.
 pub async fn spawn_anvil() -> (DynProvider<AnyNetwork>, AnvilInstance) {
@@ -29,6 +82,755 @@ pub async fn spawn_anvil() -> (DynProvider<AnyNetwork>, AnvilInstance) {
     (provider, anvil)
 }
 
+/// The result of a mocked JSON-RPC call: a result or an error message.
+type MockResponse = std::result::Result<Value, String>;
+
+/// A JSON-RPC request received by a mock server.
+#[derive(Clone)]
+struct MockRequest {
+    method: String,
+    params: Value,
+    /// The raw request body.
+    body: Vec<u8>,
+    /// The `X-Flashbots-Signature` header, if any.
+    signature: Option<String>,
+}
+
+/// Serves JSON-RPC over HTTP on a local port, answering every call with the
+/// handler, and returns its URL.
+async fn spawn_mock_server<F>(handler: F) -> reqwest::Url
+where
+    F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                let mut length = 0;
+                let mut signature = None;
+                loop {
+                    let mut line = String::new();
+                    stream.read_line(&mut line).await.unwrap();
+                    if let Some((header, value)) = line.split_once(':') {
+                        if header.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        } else if header.eq_ignore_ascii_case("x-flashbots-signature") {
+                            signature = Some(value.trim().to_string());
+                        }
+                    }
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                stream.read_exact(&mut body).await.unwrap();
+
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let mock_request = MockRequest {
+                    method: request["method"].as_str().unwrap().to_string(),
+                    params: request["params"].clone(),
+                    body,
+                    signature,
+                };
+                let response = match handler(&mock_request) {
+                    Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+                    Err(message) => json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "error": {"code": -32000, "message": message},
+                    }),
+                };
+                let body = response.to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+    url.parse().unwrap()
+}
+
+/// Serves JSON-RPC over HTTP on a local port, answering every call with the
+/// handler, and returns a provider connected to it.
+async fn spawn_mock_node<F>(handler: F) -> DynProvider<AnyNetwork>
+where
+    F: Fn(&str, &Value) -> MockResponse + Send + Sync + 'static,
+{
+    let url = spawn_mock_server(move |request| handler(&request.method, &request.params)).await;
+    let provider = ProviderBuilder::new()
+        .disable_recommended_fillers()
+        .network::<AnyNetwork>()
+        .on_http(url);
+    DynProvider::new(provider)
+}
+
+/// Parses a hex quantity from a JSON-RPC request.
+fn quantity(value: &Value) -> u64 {
+    u64::from_str_radix(value.as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+}
+
+/// A collector that emits a fixed list of events and then stays idle.
+struct VecCollector {
+    events: Vec<u64>,
+}
+
+#[async_trait]
+impl Collector<u64> for VecCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        let stream = futures::stream::iter(self.events.clone()).chain(futures::stream::pending());
+        Ok(Box::pin(stream))
+    }
+}
+
+/// A strategy that turns every event into an identical action.
+struct EchoStrategy;
+
+#[async_trait]
+impl Strategy<u64, u64> for EchoStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        vec![event]
+    }
+}
+
+/// An executor that records every action after an artificial delay.
+struct RecordingExecutor {
+    delay: Duration,
+    executed: Arc<Mutex<Vec<u64>>>,
+}
+
+#[async_trait]
+impl Executor<u64> for RecordingExecutor {
+    async fn execute(&self, action: u64) -> Result<()> {
+        tokio::time::sleep(self.delay).await;
+        self.executed.lock().unwrap().push(action);
+        Ok(())
+    }
+}
+
+/// Builds an engine wiring a [VecCollector], an [EchoStrategy] and a [RecordingExecutor].
+fn echo_engine(events: Vec<u64>, delay: Duration) -> (Engine<u64, u64>, Arc<Mutex<Vec<u64>>>) {
+    let executed = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.add_collector(Box::new(VecCollector { events }));
+    engine.add_strategy(Box::new(EchoStrategy));
+    engine.add_executor(Box::new(RecordingExecutor {
+        delay,
+        executed: executed.clone(),
+    }));
+    (engine, executed)
+}
+
+/// Test that shutting down the engine drains the actions already in flight.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_engine_shutdown_drains_pending_actions() {
+    let (engine, executed) = echo_engine((0..5).collect(), Duration::from_millis(50));
+    let shutdown = engine.shutdown_handle();
+    let mut set = engine.run().await.unwrap();
+
+    // Give the collector time to emit everything before shutting down.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let summary = shutdown.shutdown().await;
+    while set.join_next().await.is_some() {}
+
+    assert_eq!(*executed.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(summary.collectors, 1);
+    assert_eq!(summary.strategies, 1);
+    assert_eq!(summary.executors, 1);
+    assert_eq!(summary.aborted, 0);
+    assert!(summary.actions_drained > 0);
+}
+
+/// Test that executors still busy after the shutdown timeout are aborted.
+#[tokio::test]
+async fn test_engine_shutdown_aborts_after_timeout() {
+    let (engine, executed) = echo_engine(vec![1], Duration::from_secs(60));
+    let engine = engine.with_shutdown_timeout(Duration::from_millis(100));
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let summary = shutdown.shutdown().await;
+
+    assert!(executed.lock().unwrap().is_empty());
+    assert_eq!(summary.executors, 0);
+    assert_eq!(summary.aborted, 1);
+}
+
+/// A collector whose stream never starts, like a stalled subscription.
+struct HangingCollector;
+
+#[async_trait]
+impl Collector<u64> for HangingCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        futures::future::pending().await
+    }
+}
+
+/// Test that a collector stuck starting its stream does not hold up shutdown.
+#[tokio::test]
+async fn test_engine_shutdown_stops_starting_collectors() {
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_collector(Box::new(HangingCollector));
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let summary = tokio::time::timeout(Duration::from_secs(1), shutdown.shutdown())
+        .await
+        .expect("shutdown hung on the collector");
+
+    assert_eq!(summary.collectors, 1);
+    assert_eq!(summary.aborted, 0);
+}
+
+/// A collector that blocks its thread while starting, ignoring cancellation.
+struct BlockingCollector;
+
+#[async_trait]
+impl Collector<u64> for BlockingCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        std::thread::sleep(Duration::from_millis(500));
+        Ok(Box::pin(futures::stream::pending()))
+    }
+}
+
+/// Test that collectors still running after the shutdown timeout are aborted.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_engine_shutdown_aborts_collectors_after_timeout() {
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_collector(Box::new(BlockingCollector));
+    let engine = engine.with_shutdown_timeout(Duration::from_millis(50));
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let summary = tokio::time::timeout(Duration::from_millis(300), shutdown.shutdown())
+        .await
+        .expect("shutdown waited for the collector");
+
+    assert_eq!(summary.collectors, 0);
+    assert_eq!(summary.aborted, 1);
+}
+
+/// A strategy that is slow on its first event and records every lag notification.
+#[derive(Default)]
+struct SlowStrategy {
+    syncs: Arc<Mutex<u64>>,
+    missed: Arc<Mutex<Vec<u64>>>,
+    first_done: bool,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for SlowStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        *self.syncs.lock().unwrap() += 1;
+        Ok(())
+    }
+
+    async fn process_event(&mut self, _event: u64) -> Vec<u64> {
+        if !self.first_done {
+            self.first_done = true;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        vec![]
+    }
+
+    async fn on_lag(&mut self, missed: u64) {
+        self.missed.lock().unwrap().push(missed);
+    }
+}
+
+/// Test that a lagging strategy is told how many events it missed and resyncs.
+#[tokio::test]
+async fn test_engine_strategy_lag_resyncs() {
+    let strategy = SlowStrategy::default();
+    let (syncs, missed) = (strategy.syncs.clone(), strategy.missed.clone());
+    let mut engine: Engine<u64, u64> = Engine::new().with_event_channel_capacity(4);
+    engine.add_collector(Box::new(VecCollector {
+        events: (0..32).collect(),
+    }));
+    let lagged = engine
+        .add_strategy(Box::new(strategy))
+        .with_lag_policy(LagPolicy::Resync)
+        .lag_counter();
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.shutdown().await;
+
+    assert!(lagged.get() > 0);
+    assert_eq!(missed.lock().unwrap().iter().sum::<u64>(), lagged.get());
+    assert_eq!(
+        *syncs.lock().unwrap(),
+        1 + missed.lock().unwrap().len() as u64
+    );
+}
+
+/// Test that a fail-fast strategy shuts the engine down when it lags.
+#[tokio::test]
+async fn test_engine_strategy_lag_fails_fast() {
+    let mut engine: Engine<u64, u64> = Engine::new().with_event_channel_capacity(4);
+    engine.add_collector(Box::new(VecCollector {
+        events: (0..32).collect(),
+    }));
+    engine
+        .add_strategy(Box::new(SlowStrategy::default()))
+        .with_lag_policy(LagPolicy::FailFast);
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+
+    let summary = tokio::time::timeout(Duration::from_secs(5), shutdown.wait())
+        .await
+        .expect("engine should stop on lag");
+    assert_eq!(summary.strategies, 1);
+}
+
+/// A collector that fails to start a number of times, then emits a single
+/// event per stream before the stream ends.
+struct FlakyCollector {
+    failures: u32,
+    attempts: AtomicU32,
+}
+
+#[async_trait]
+impl Collector<u64> for FlakyCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+        if attempt < self.failures {
+            return Err(anyhow::anyhow!("connection refused").into());
+        }
+        Ok(Box::pin(futures::stream::iter([attempt as u64])))
+    }
+}
+
+/// Builds a restart policy with short backoffs suitable for tests.
+fn fast_restarts(max_restarts: Option<u32>) -> RestartPolicy {
+    RestartPolicy {
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(20),
+        max_restarts,
+    }
+}
+
+/// Test that collectors are restarted when they fail and when their stream ends.
+#[tokio::test]
+async fn test_engine_restarts_collectors() {
+    let (mut engine, executed) = echo_engine(vec![], Duration::ZERO);
+    let restarts = engine
+        .add_collector(Box::new(FlakyCollector {
+            failures: 2,
+            attempts: AtomicU32::new(0),
+        }))
+        .with_restart_policy(fast_restarts(None))
+        .restart_counter();
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    shutdown.shutdown().await;
+
+    assert!(restarts.get() >= 3);
+    assert!(executed.lock().unwrap().starts_with(&[2, 3]));
+}
+
+/// Test that a collector which keeps failing gives up, stopping the engine.
+#[tokio::test]
+async fn test_engine_collector_gives_up() {
+    let mut engine: Engine<u64, u64> = Engine::new();
+    let restarts = engine
+        .add_collector(Box::new(FlakyCollector {
+            failures: u32::MAX,
+            attempts: AtomicU32::new(0),
+        }))
+        .with_restart_policy(fast_restarts(Some(2)))
+        .restart_counter();
+    let shutdown = engine.shutdown_handle();
+    let status = engine.status_handle();
+    let _set = engine.run().await.unwrap();
+
+    let summary = tokio::time::timeout(Duration::from_secs(5), shutdown.wait())
+        .await
+        .expect("engine should stop once the collector gives up");
+    assert_eq!(summary.collectors, 1);
+    assert_eq!(restarts.get(), 2);
+    let collector = &status.snapshot()[0];
+    assert_eq!(collector.state, ComponentState::Failed);
+    assert_eq!(collector.errors, 3);
+}
+
+/// A strategy whose initial sync always fails.
+struct BrokenStrategy;
+
+#[async_trait]
+impl Strategy<u64, u64> for BrokenStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Err(anyhow::anyhow!("rpc unavailable").into())
+    }
+
+    async fn process_event(&mut self, _event: u64) -> Vec<u64> {
+        vec![]
+    }
+}
+
+/// Test that a failing strategy sync identifies the strategy.
+#[tokio::test]
+async fn test_engine_reports_failed_strategy_sync() {
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_strategy(Box::new(EchoStrategy));
+    engine
+        .add_strategy(Box::new(BrokenStrategy))
+        .with_name("broken");
+
+    match engine.run().await {
+        Err(artemis_core::Error::StrategySync { strategy, .. }) => assert_eq!(strategy, "broken"),
+        other => panic!(
+            "expected a strategy sync error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+/// Test that node errors returned on submission are classified.
+#[test]
+fn test_error_classifies_submission_rejections() {
+    let rejection = |message: &'static str| {
+        let err: TransportError = RpcError::ErrorResp(ErrorPayload {
+            code: -32000,
+            message: message.into(),
+            data: None,
+        });
+        artemis_core::Error::from_submission(err)
+            .rejection()
+            .cloned()
+    };
+
+    assert_eq!(rejection("nonce too low"), Some(Rejection::NonceTooLow));
+    assert_eq!(
+        rejection("replacement transaction underpriced"),
+        Some(Rejection::Underpriced)
+    );
+    assert_eq!(
+        rejection("insufficient funds for gas * price + value"),
+        Some(Rejection::InsufficientFunds)
+    );
+    assert_eq!(
+        rejection("execution reverted: too little received"),
+        Some(Rejection::Reverted(
+            "execution reverted: too little received".to_string()
+        ))
+    );
+    assert_eq!(rejection("header not found"), None);
+}
+
+/// Test that only transient errors are retryable, not node error responses.
+#[test]
+fn test_error_retryable() {
+    let node_error: TransportError = RpcError::ErrorResp(ErrorPayload {
+        code: -32000,
+        message: "intrinsic gas too low".into(),
+        data: None,
+    });
+    let node_error = artemis_core::Error::from_submission(node_error);
+    assert!(matches!(node_error, Error::Transport(_)));
+    assert!(!node_error.is_retryable());
+
+    let dropped: TransportError = RpcError::NullResp;
+    assert!(Error::from(dropped).is_retryable());
+    assert!(Error::Timeout(Duration::ZERO).is_retryable());
+    assert!(!Error::Rejected(Rejection::Underpriced).is_retryable());
+}
+
+/// Test that the status handle reports each component by name.
+#[tokio::test]
+async fn test_engine_reports_component_status() {
+    let executed = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine
+        .add_collector(Box::new(VecCollector {
+            events: vec![1, 2, 3],
+        }))
+        .with_name("numbers");
+    engine
+        .add_strategy(Box::new(EchoStrategy))
+        .with_name("echo");
+    engine
+        .add_executor(Box::new(RecordingExecutor {
+            delay: Duration::ZERO,
+            executed: executed.clone(),
+        }))
+        .with_name("recorder");
+    let shutdown = engine.shutdown_handle();
+    let mut status = engine.status_handle();
+    assert!(status.snapshot().is_empty());
+
+    let _set = engine.run().await.unwrap();
+    while !status.is_healthy() {
+        status.changed().await;
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let collector = status.component("numbers").unwrap();
+    assert_eq!(collector.kind, ComponentKind::Collector);
+    assert_eq!(collector.processed, 3);
+    assert!(collector.last_event.is_some());
+    assert_eq!(status.component("echo").unwrap().processed, 3);
+    assert_eq!(status.component("recorder").unwrap().processed, 3);
+
+    shutdown.shutdown().await;
+    assert!(status
+        .snapshot()
+        .iter()
+        .all(|component| component.state == ComponentState::Stopped));
+}
+
+/// Test that the engine records metrics and serves them over HTTP.
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn test_engine_records_metrics() {
+    use artemis_core::metrics::{serve, EngineMetrics};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let registry = prometheus::Registry::new();
+    let metrics = EngineMetrics::new(&registry).unwrap();
+    let (engine, _) = echo_engine(vec![1, 2], Duration::ZERO);
+    let engine = engine.with_metrics(metrics);
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    shutdown.shutdown().await;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    tokio::spawn(serve(registry, addr));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains(r#"artemis_collector_events_total{collector="collector-0"} 2"#));
+    assert!(response.contains(r#"artemis_strategy_actions_total{strategy="strategy-0"} 2"#));
+    assert!(response
+        .contains(r#"artemis_executor_actions_total{executor="executor-0",result="success"} 2"#));
+}
+
+/// An executor that fails every action.
+struct FailingExecutor;
+
+#[async_trait]
+impl Executor<u64> for FailingExecutor {
+    async fn execute(&self, _action: u64) -> Result<()> {
+        Err(anyhow::anyhow!("boom").into())
+    }
+}
+
+/// A log sink shared between the test and the tracing subscriber.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Test that executor errors are logged with the event and strategy they came from.
+#[tokio::test]
+async fn test_engine_traces_actions_to_their_event() {
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mut engine = Engine::new();
+    engine
+        .add_collector(Box::new(VecCollector { events: vec![7] }))
+        .with_name("numbers");
+    engine
+        .add_strategy(Box::new(EchoStrategy))
+        .with_name("echo");
+    engine
+        .add_executor(Box::new(FailingExecutor))
+        .with_name("failing");
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    shutdown.shutdown().await;
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let line = logs
+        .lines()
+        .find(|line| line.contains("error executing action: boom"))
+        .expect("executor error should be logged");
+    assert!(line.contains("execute{correlation_id="));
+    assert!(line.contains("source=numbers strategy=echo executor=failing"));
+}
+
+/// Test that events and actions are only delivered to the components whose
+/// filters accept them.
+#[tokio::test]
+async fn test_engine_routes_by_filter() {
+    let small = Arc::new(Mutex::new(vec![]));
+    let all = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.add_collector(Box::new(VecCollector {
+        events: (0..6).collect(),
+    }));
+    engine
+        .add_strategy(Box::new(EchoStrategy))
+        .with_name("even")
+        .with_event_filter(|event| event % 2 == 0);
+    engine
+        .add_strategy(Box::new(EchoStrategy))
+        .with_name("odd")
+        .with_event_filter(|event| event % 2 == 1);
+    engine
+        .add_executor(Box::new(RecordingExecutor {
+            delay: Duration::ZERO,
+            executed: small.clone(),
+        }))
+        .with_name("small")
+        .with_action_filter(|action| *action < 3);
+    engine.add_executor(Box::new(RecordingExecutor {
+        delay: Duration::ZERO,
+        executed: all.clone(),
+    }));
+    let status = engine.status_handle();
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    shutdown.shutdown().await;
+
+    assert_eq!(status.component("even").unwrap().processed, 3);
+    assert_eq!(status.component("odd").unwrap().processed, 3);
+    let mut small = small.lock().unwrap().clone();
+    small.sort();
+    assert_eq!(small, vec![0, 1, 2]);
+    let mut all = all.lock().unwrap().clone();
+    all.sort();
+    assert_eq!(all, vec![0, 1, 2, 3, 4, 5]);
+}
+
+/// Test that an executor runs actions concurrently up to its limit.
+#[tokio::test]
+async fn test_engine_executes_actions_concurrently() {
+    let executed = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.add_collector(Box::new(VecCollector {
+        events: (0..4).collect(),
+    }));
+    engine.add_strategy(Box::new(EchoStrategy));
+    engine
+        .add_executor(Box::new(RecordingExecutor {
+            delay: Duration::from_millis(100),
+            executed: executed.clone(),
+        }))
+        .with_concurrency(4);
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    assert_eq!(executed.lock().unwrap().len(), 4);
+    shutdown.shutdown().await;
+}
+
+/// An executor that takes longer on smaller actions and records the order in
+/// which they complete.
+struct ReversingExecutor {
+    executed: Arc<Mutex<Vec<u64>>>,
+}
+
+#[async_trait]
+impl Executor<u64> for ReversingExecutor {
+    async fn execute(&self, action: u64) -> Result<()> {
+        tokio::time::sleep(Duration::from_millis(60 - action * 10)).await;
+        self.executed.lock().unwrap().push(action);
+        Ok(())
+    }
+}
+
+/// Test that actions sharing an ordering key are executed in order, while
+/// other actions still run concurrently.
+#[tokio::test]
+async fn test_engine_keeps_actions_in_order_per_key() {
+    let executed = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.add_collector(Box::new(VecCollector {
+        events: (0..6).collect(),
+    }));
+    engine.add_strategy(Box::new(EchoStrategy));
+    engine
+        .add_executor(Box::new(ReversingExecutor {
+            executed: executed.clone(),
+        }))
+        .with_concurrency(6)
+        .with_ordering_key(|action| action % 2);
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    shutdown.shutdown().await;
+
+    let executed = executed.lock().unwrap().clone();
+    let evens: Vec<_> = executed.iter().copied().filter(|a| a % 2 == 0).collect();
+    let odds: Vec<_> = executed.iter().copied().filter(|a| a % 2 == 1).collect();
+    assert_eq!(evens, vec![0, 2, 4]);
+    assert_eq!(odds, vec![1, 3, 5]);
+    // The two keys were executed side by side.
+    assert_eq!(executed[..2], [1, 0]);
+}
+
+/// Test that actions waiting for an earlier action with the same key do not
+/// hold the permits actions with other keys need.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_engine_ordering_does_not_block_other_keys() {
+    let executed = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.add_collector(Box::new(VecCollector {
+        events: vec![0, 2, 1],
+    }));
+    engine.add_strategy(Box::new(EchoStrategy));
+    engine
+        .add_executor(Box::new(RecordingExecutor {
+            delay: Duration::from_millis(30),
+            executed: executed.clone(),
+        }))
+        .with_concurrency(1)
+        .with_ordering_key(|action| action % 2);
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    shutdown.shutdown().await;
+
+    assert_eq!(*executed.lock().unwrap(), vec![0, 1, 2]);
+}
+
 /// Test that block collector correctly emits blocks.
 #[tokio::test]
 async fn test_block_collector_sends_blocks() {
@@ -76,7 +878,7 @@ async fn test_mempool_collector_sends_txs() {
         .await
         .unwrap();
     let tx = mempool_stream.into_future().await.0.unwrap();
-    assert_eq!(tx.value(), value.into());
+    assert_eq!(tx.value(), value);
 }
 
 /// Test that the mempool executor correctly sends txs
@@ -104,3 +906,1304 @@ async fn test_mempool_executor_sends_tx_simple() {
     let count = provider.get_transaction_count(account).await.unwrap();
     assert_eq!(count, 1);
 }
+
+/// A mock node for an account whose pending nonce starts at 5 and that only
+/// accepts nonces from `min_nonce` onwards, recording every submitted nonce.
+async fn spawn_nonce_node(min_nonce: u64) -> (Arc<DynProvider<AnyNetwork>>, Arc<Mutex<Vec<u64>>>) {
+    let sent = Arc::new(Mutex::new(vec![]));
+    let recorded = sent.clone();
+    let count = Arc::new(Mutex::new(5));
+    let provider = spawn_mock_node(move |method, params| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_getTransactionCount" => Ok(json!(format!("{:#x}", *count.lock().unwrap()))),
+        "eth_sendTransaction" => {
+            let nonce = quantity(&params[0]["nonce"]);
+            recorded.lock().unwrap().push(nonce);
+            if nonce < min_nonce {
+                // The account was used elsewhere in the meantime.
+                *count.lock().unwrap() = min_nonce;
+                return Err("nonce too low".to_string());
+            }
+            Ok(json!(format!("{:#066x}", nonce)))
+        }
+        _ => fee_response(method),
+    })
+    .await;
+    (Arc::new(provider), sent)
+}
+
+/// Answers the fee queries of a mock node whose next block's base fee is 110
+/// wei, suggesting a priority fee of 2 wei and a legacy gas price of 100 wei.
+fn fee_response(method: &str) -> MockResponse {
+    match method {
+        "eth_feeHistory" => Ok(json!({
+            "oldestBlock": "0x1",
+            "baseFeePerGas": ["0x64", "0x6e"],
+            "gasUsedRatio": [0.5],
+        })),
+        "eth_maxPriorityFeePerGas" => Ok(json!("0x2")),
+        "eth_gasPrice" => Ok(json!("0x64")),
+        _ => Err(format!("unexpected method {method}")),
+    }
+}
+
+/// Sends the action through a mempool executor and returns the transaction
+/// submitted to the node.
+async fn submitted_tx(
+    executor: impl FnOnce(Arc<DynProvider<AnyNetwork>>) -> MempoolExecutor,
+    action: SubmitTxToMempool,
+) -> Result<Value> {
+    let sent = Arc::new(Mutex::new(None));
+    let recorded = sent.clone();
+    let provider = spawn_mock_node(move |method, params| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_sendTransaction" => {
+            *recorded.lock().unwrap() = Some(params[0].clone());
+            Ok(json!(format!("{:#066x}", 1)))
+        }
+        _ => fee_response(method),
+    })
+    .await;
+    executor(Arc::new(provider)).execute(action).await?;
+    let tx = sent.lock().unwrap().take();
+    Ok(tx.unwrap())
+}
+
+/// Builds a transaction bidding a share of the profit, expressed per gas for
+/// the 21000 gas estimated by the mock node.
+fn bid_tx(profit_per_gas: u64, bid_percentage: u64) -> SubmitTxToMempool {
+    let mut action = submit_tx(Some(0));
+    action.gas_bid_info = Some(GasBidInfo {
+        total_profit: U128::from(profit_per_gas) * U128::from(21000),
+        bid_percentage: U128::from(bid_percentage),
+    });
+    action
+}
+
+/// Test that the mempool executor sends type-2 transactions priced from the
+/// next block's base fee, and legacy transactions when asked to.
+#[tokio::test]
+async fn test_mempool_executor_prices_gas() {
+    // Without a bid, the suggested priority fee with room for the base fee to double.
+    let tx = submitted_tx(MempoolExecutor::new, submit_tx(Some(0)))
+        .await
+        .unwrap();
+    assert_eq!(tx["maxPriorityFeePerGas"], "0x2");
+    assert_eq!(tx["maxFeePerGas"], json!(format!("{:#x}", 2 * 110 + 2)));
+    assert!(tx.get("gasPrice").is_none());
+
+    // With a bid, the share of the profit left after the next base fee.
+    let tx = submitted_tx(MempoolExecutor::new, bid_tx(1000, 50))
+        .await
+        .unwrap();
+    assert_eq!(tx["maxPriorityFeePerGas"], json!(format!("{:#x}", 445)));
+    assert_eq!(tx["maxFeePerGas"], json!(format!("{:#x}", 110 + 445)));
+
+    let capped = |client| {
+        MempoolExecutor::new(client).with_fee_caps(FeeCaps {
+            max_priority_fee_per_gas: Some(40),
+            max_fee_per_gas: Some(120),
+        })
+    };
+    let tx = submitted_tx(capped, bid_tx(1000, 50)).await.unwrap();
+    assert_eq!(tx["maxPriorityFeePerGas"], json!(format!("{:#x}", 40)));
+    assert_eq!(tx["maxFeePerGas"], json!(format!("{:#x}", 120)));
+
+    let legacy = |client| MempoolExecutor::new(client).with_gas_pricing(GasPricing::Legacy);
+    let tx = submitted_tx(legacy, bid_tx(1000, 50)).await.unwrap();
+    assert_eq!(tx["gasPrice"], json!(format!("{:#x}", 500)));
+    assert!(tx.get("maxFeePerGas").is_none());
+}
+
+/// Test that the mempool executor refuses invalid bids and bids that would
+/// spend too much of the profit on gas.
+#[tokio::test]
+async fn test_mempool_executor_guards_bids() {
+    let bid_error = |result: Result<Value>| match result {
+        Err(Error::Bid(e)) => e,
+        other => panic!("expected a bid error, got {other:?}"),
+    };
+
+    let err = bid_error(submitted_tx(MempoolExecutor::new, bid_tx(1000, 101)).await);
+    assert_eq!(err, BidError::InvalidBidPercentage(U128::from(101)));
+
+    // The whole of the largest profit can be bid without overflowing.
+    let mut largest = bid_tx(0, 100);
+    largest.gas_bid_info.as_mut().unwrap().total_profit = U128::MAX;
+    let tx = submitted_tx(MempoolExecutor::new, largest).await.unwrap();
+    assert_eq!(
+        tx["maxFeePerGas"],
+        json!(format!("{:#x}", u128::MAX / 21000))
+    );
+
+    // A 100% bid spends the whole profit, base fee included.
+    let tx = submitted_tx(MempoolExecutor::new, bid_tx(1000, 100))
+        .await
+        .unwrap();
+    assert_eq!(tx["maxPriorityFeePerGas"], json!(format!("{:#x}", 890)));
+    assert_eq!(tx["maxFeePerGas"], json!(format!("{:#x}", 1000)));
+
+    // The base fee alone costs the whole profit.
+    let err = bid_error(submitted_tx(MempoolExecutor::new, bid_tx(110, 50)).await);
+    assert_eq!(
+        err,
+        BidError::BaseFeeAboveBreakeven {
+            base_fee: U128::from(110),
+            breakeven_gas_price: U128::from(110),
+        }
+    );
+
+    // The max fee follows the capped tip, so a rising base fee cannot make
+    // the transaction pay more than the profit.
+    let tip_capped = |client| {
+        MempoolExecutor::new(client).with_fee_caps(FeeCaps {
+            max_priority_fee_per_gas: Some(40),
+            max_fee_per_gas: None,
+        })
+    };
+    let tx = submitted_tx(tip_capped, bid_tx(1000, 100)).await.unwrap();
+    assert_eq!(tx["maxPriorityFeePerGas"], json!(format!("{:#x}", 40)));
+    assert_eq!(tx["maxFeePerGas"], json!(format!("{:#x}", 110 + 40)));
+
+    // 1000 - (110 + 445) = 445 wei per gas left, below the minimum of 450.
+    let floor = |client| {
+        MempoolExecutor::new(client).with_min_net_profit(U128::from(450) * U128::from(21000))
+    };
+    let err = bid_error(submitted_tx(floor, bid_tx(1000, 50)).await);
+    assert_eq!(
+        err,
+        BidError::ProfitBelowMinimum {
+            total_profit: U128::from(21_000_000),
+            gas_cost: U128::from(555 * 21000),
+            min_net_profit: U128::from(450 * 21000),
+        }
+    );
+
+    let capped = |client| MempoolExecutor::new(client).with_max_gas_price(500);
+    let err = bid_error(submitted_tx(capped, bid_tx(1000, 50)).await);
+    assert_eq!(
+        err,
+        BidError::GasPriceAboveCap {
+            gas_price: 555,
+            cap: 500
+        }
+    );
+}
+
+/// Builds a transaction from a fixed account, optionally with an explicit nonce.
+fn submit_tx(nonce: Option<u64>) -> SubmitTxToMempool {
+    let account = Address::repeat_byte(1);
+    let mut tx = TransactionRequest::default()
+        .with_from(account)
+        .with_to(account);
+    tx.nonce = nonce;
+    SubmitTxToMempool {
+        tx: WithOtherFields::new(tx),
+        gas_bid_info: None,
+    }
+}
+
+/// Test that the mempool executor signs transactions locally with the signer
+/// matching their sender.
+#[tokio::test]
+async fn test_mempool_executor_signs_locally() {
+    // The first two accounts of the test mnemonic, loaded in different ways.
+    let mnemonic = "test test test test test test test test test test test junk";
+    let first_signer = signers::from_mnemonic(mnemonic, 0).unwrap();
+    let second_signer = signers::from_private_key(
+        "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    )
+    .unwrap();
+    let (first, second) = (first_signer.address(), second_signer.address());
+    assert_eq!(
+        second,
+        signers::from_mnemonic(mnemonic, 1).unwrap().address()
+    );
+    let wallet = signers::wallet([first_signer, second_signer]).unwrap();
+
+    let sent = Arc::new(Mutex::new(vec![]));
+    let recorded = sent.clone();
+    let provider = spawn_mock_node(move |method, params| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_chainId" => Ok(json!("0x1")),
+        "eth_getTransactionCount" => Ok(json!("0x5")),
+        "eth_sendRawTransaction" => {
+            let raw: Bytes = serde_json::from_value(params[0].clone()).unwrap();
+            let tx = TxEnvelope::decode_2718(&mut raw.as_ref()).unwrap();
+            let signer = tx.recover_signer().unwrap();
+            recorded
+                .lock()
+                .unwrap()
+                .push((signer, tx.nonce(), tx.chain_id(), tx.gas_limit()));
+            Ok(json!(tx.tx_hash()))
+        }
+        _ => fee_response(method),
+    })
+    .await;
+    let executor = MempoolExecutor::new(Arc::new(provider)).with_wallet(wallet);
+
+    // Transactions are signed by their sender, or by the default signer.
+    let mut action = submit_tx(None);
+    action.tx.from = Some(second);
+    executor.execute(action).await.unwrap();
+    let mut action = submit_tx(None);
+    action.tx.from = None;
+    executor.execute(action).await.unwrap();
+    assert_eq!(
+        *sent.lock().unwrap(),
+        [(second, 5, Some(1), 21000), (first, 5, Some(1), 21000)]
+    );
+
+    // Transactions from accounts without a signer are not sent.
+    assert!(executor.execute(submit_tx(None)).await.is_err());
+    assert_eq!(sent.lock().unwrap().len(), 2);
+}
+
+/// Test that the mempool executor in dry-run mode prepares and signs
+/// transactions without sending them.
+#[tokio::test]
+async fn test_mempool_executor_dry_run() {
+    let signer = PrivateKeySigner::random();
+    let sender = signer.address();
+    let provider = spawn_mock_node(|method, _| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_chainId" => Ok(json!("0x1")),
+        "eth_getTransactionCount" => Ok(json!("0x5")),
+        _ => fee_response(method),
+    })
+    .await;
+    let dry_run = DryRun::new();
+    let executor = MempoolExecutor::new(Arc::new(provider))
+        .with_wallet(EthereumWallet::from(signer))
+        .with_dry_run(dry_run.clone());
+
+    let mut action = submit_tx(None);
+    action.tx.from = Some(sender);
+    executor.execute(action.clone()).await.unwrap();
+    executor.execute(action).await.unwrap();
+    executor
+        .execute(SpeedUpTx { sender, nonce: 6 })
+        .await
+        .unwrap();
+
+    let txs = dry_run.take();
+    let nonces: Vec<_> = txs.iter().map(|sent| sent.tx.nonce.unwrap()).collect();
+    assert_eq!(nonces, [5, 6, 6]);
+    assert_eq!(txs[1].tx.max_fee_per_gas, Some(222));
+    assert_eq!(txs[2].tx.max_fee_per_gas, Some(250));
+    for sent in &txs {
+        let raw = sent.raw.as_ref().unwrap();
+        let tx = TxEnvelope::decode_2718(&mut raw.as_ref()).unwrap();
+        assert_eq!(tx.recover_signer().unwrap(), sender);
+        assert_eq!(sent.hash, Some(*tx.tx_hash()));
+    }
+}
+
+/// Test that the mempool executor assigns consecutive nonces per sender and
+/// keeps explicit nonces as they are.
+#[tokio::test]
+async fn test_mempool_executor_manages_nonces() {
+    let (provider, sent) = spawn_nonce_node(0).await;
+    let executor = MempoolExecutor::new(provider);
+
+    executor.execute(submit_tx(None)).await.unwrap();
+    executor.execute(submit_tx(None)).await.unwrap();
+    // Replaces the first transaction.
+    executor.execute(submit_tx(Some(5))).await.unwrap();
+    executor.execute(submit_tx(None)).await.unwrap();
+
+    assert_eq!(*sent.lock().unwrap(), vec![5, 6, 5, 7]);
+}
+
+/// Test that the mempool executor resyncs its nonces when one is rejected.
+#[tokio::test]
+async fn test_mempool_executor_resyncs_nonce_too_low() {
+    let (provider, sent) = spawn_nonce_node(9).await;
+    let nonces = NonceManager::new(provider.clone());
+    let executor = MempoolExecutor::new(provider).with_nonce_manager(nonces.clone());
+
+    executor.execute(submit_tx(None)).await.unwrap();
+    executor.execute(submit_tx(None)).await.unwrap();
+
+    assert_eq!(*sent.lock().unwrap(), vec![5, 9, 10]);
+    assert_eq!(
+        nonces.peek_nonce(Address::repeat_byte(1)).await.unwrap(),
+        11
+    );
+}
+
+/// Test that a nonce is only released if no later nonce was handed out.
+#[tokio::test]
+async fn test_nonce_manager_releases_latest_nonce() {
+    let (provider, _) = spawn_nonce_node(0).await;
+    let nonces = NonceManager::new(provider);
+    let sender = Address::repeat_byte(1);
+
+    assert_eq!(nonces.next_nonce(sender).await.unwrap(), 5);
+    assert_eq!(nonces.next_nonce(sender).await.unwrap(), 6);
+    assert!(!nonces.release(sender, 5).await);
+    assert_eq!(nonces.peek_nonce(sender).await.unwrap(), 7);
+    assert!(nonces.release(sender, 6).await);
+    assert_eq!(nonces.peek_nonce(sender).await.unwrap(), 6);
+}
+
+/// Test that fetching the nonce of one sender does not hold up the others.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_nonce_manager_locks_per_sender() {
+    let slow = Address::repeat_byte(1);
+    let provider = spawn_mock_node(move |method, params| match method {
+        "eth_getTransactionCount" => {
+            if params[0].as_str().unwrap().parse::<Address>().unwrap() == slow {
+                std::thread::sleep(Duration::from_millis(500));
+            }
+            Ok(json!("0x5"))
+        }
+        _ => Err(format!("unexpected method {method}")),
+    })
+    .await;
+    let nonces = NonceManager::new(Arc::new(provider));
+
+    let slow_nonce = tokio::spawn({
+        let nonces = nonces.clone();
+        async move { nonces.next_nonce(slow).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let fast_nonce = tokio::time::timeout(
+        Duration::from_millis(200),
+        nonces.next_nonce(Address::repeat_byte(2)),
+    )
+    .await
+    .expect("waited for the other sender's nonce");
+
+    assert_eq!(fast_nonce.unwrap(), 5);
+    assert_eq!(slow_nonce.await.unwrap().unwrap(), 5);
+}
+
+/// Test that a rejected transaction gives back its nonce without resyncing,
+/// which would hand out nonces still used by transactions in flight.
+#[tokio::test]
+async fn test_mempool_executor_keeps_in_flight_nonces() {
+    let provider = spawn_mock_node(|method, _| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_getTransactionCount" => Ok(json!("0x5")),
+        "eth_sendTransaction" => Err("transaction underpriced".to_string()),
+        _ => fee_response(method),
+    })
+    .await;
+    let provider = Arc::new(provider);
+    let nonces = NonceManager::new(provider.clone());
+    let executor = MempoolExecutor::new(provider).with_nonce_manager(nonces.clone());
+    let sender = Address::repeat_byte(1);
+
+    // Reserved by a transaction that has not reached the node yet.
+    assert_eq!(nonces.next_nonce(sender).await.unwrap(), 5);
+    let err = executor.execute(submit_tx(None)).await.unwrap_err();
+
+    assert_eq!(err.rejection(), Some(&Rejection::Underpriced));
+    assert_eq!(nonces.peek_nonce(sender).await.unwrap(), 6);
+}
+
+/// Test that a failed nonce resync does not hide the rejection that caused it.
+#[tokio::test]
+async fn test_mempool_executor_reports_rejection_if_resync_fails() {
+    let counts = Arc::new(Mutex::new(0));
+    let provider = spawn_mock_node(move |method, _| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_getTransactionCount" => {
+            let mut counts = counts.lock().unwrap();
+            *counts += 1;
+            if *counts > 1 {
+                return Err("node unavailable".to_string());
+            }
+            Ok(json!("0x5"))
+        }
+        "eth_sendTransaction" => Err("nonce too low".to_string()),
+        _ => fee_response(method),
+    })
+    .await;
+    let executor = MempoolExecutor::new(Arc::new(provider));
+
+    let err = executor.execute(submit_tx(None)).await.unwrap_err();
+
+    assert_eq!(err.rejection(), Some(&Rejection::NonceTooLow));
+    assert!(!err.is_retryable());
+}
+
+/// Sends the action through a mempool executor simulating it on a mock node,
+/// answering `debug_traceCall` and `eth_simulateV1` with the given results,
+/// and returns whether the transaction was submitted.
+async fn simulate_tx(
+    simulation: Simulation,
+    trace: MockResponse,
+    simulated: MockResponse,
+) -> Result<bool> {
+    let sent = Arc::new(Mutex::new(false));
+    let recorded = sent.clone();
+    let provider = spawn_mock_node(move |method, params| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        // Simulations must run against the pending block.
+        "eth_call" | "debug_traceCall" | "eth_simulateV1" if params[1] != "pending" => {
+            Err(format!("{method} not simulated against the pending block"))
+        }
+        "eth_call" => Ok(json!("0x")),
+        "debug_traceCall" => trace.clone(),
+        "eth_simulateV1" => simulated.clone(),
+        "eth_sendTransaction" => {
+            *recorded.lock().unwrap() = true;
+            Ok(json!(format!("{:#066x}", 1)))
+        }
+        _ => fee_response(method),
+    })
+    .await;
+    MempoolExecutor::new(Arc::new(provider))
+        .with_simulation(simulation)
+        .execute(submit_tx(Some(0)))
+        .await?;
+    let sent = *sent.lock().unwrap();
+    Ok(sent)
+}
+
+/// Builds an `eth_simulateV1` result for a transaction moving the token
+/// balance from `before` to `after`.
+fn simulated_balances(before: u64, after: u64) -> MockResponse {
+    let balance = |balance: u64| {
+        json!({
+            "returnData": format!("{:#066x}", balance),
+            "logs": [],
+            "gasUsed": "0x5208",
+            "status": "0x1",
+        })
+    };
+    let tx = json!({"returnData": "0x", "logs": [], "gasUsed": "0x5208", "status": "0x1"});
+    Ok(json!([{ "calls": [balance(before), tx, balance(after)] }]))
+}
+
+/// Test that the mempool executor rejects transactions whose simulation
+/// reverts or does not make the expected profit.
+#[tokio::test]
+async fn test_mempool_executor_simulates_txs() {
+    let unused = || Err("unused".to_string());
+    let rejection = |result: Result<bool>| match result {
+        Err(Error::Rejected(rejection)) => rejection,
+        other => panic!("expected a rejection, got {other:?}"),
+    };
+
+    // Plain eth_call succeeds.
+    assert!(simulate_tx(Simulation::new(), unused(), unused())
+        .await
+        .unwrap());
+
+    // The trace reports the revert reason.
+    let reverted = Ok(json!({
+        "from": format!("{:#x}", Address::repeat_byte(1)),
+        "input": "0x",
+        "type": "CALL",
+        "error": "execution reverted",
+        "revertReason": "too late",
+    }));
+    let result = simulate_tx(Simulation::new().with_trace(), reverted, unused()).await;
+    assert_eq!(
+        rejection(result),
+        Rejection::Reverted("too late".to_string())
+    );
+
+    // Nodes without the debug API fall back to eth_call.
+    let unsupported = Err("the method debug_traceCall does not exist/is not available".to_string());
+    assert!(
+        simulate_tx(Simulation::new().with_trace(), unsupported, unused())
+            .await
+            .unwrap()
+    );
+
+    let token = Address::repeat_byte(2);
+    let holder = Address::repeat_byte(1);
+    let profit =
+        |min_delta| Simulation::new().with_profit_check(token, holder, U256::from(min_delta));
+    assert!(
+        simulate_tx(profit(50), unused(), simulated_balances(100, 150))
+            .await
+            .unwrap()
+    );
+    let result = simulate_tx(profit(51), unused(), simulated_balances(100, 150)).await;
+    assert_eq!(
+        rejection(result),
+        Rejection::InsufficientProfit {
+            before: U256::from(100),
+            after: U256::from(150),
+            min_delta: U256::from(51),
+        }
+    );
+}
+
+/// Sends a transaction with nonce 3 through a tracking mempool executor and
+/// returns its outcome. The mock node fails the given receipt poll, returns
+/// the receipt from the given poll onwards, and reports `mined` transactions
+/// from the sender.
+async fn track_tx(
+    failed_poll: Option<usize>,
+    receipt_from_poll: Option<usize>,
+    mined: u64,
+) -> TxOutcome {
+    let polls = Arc::new(AtomicU32::new(0));
+    let provider = spawn_mock_node(move |method, _| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_sendTransaction" => Ok(json!(format!("{:#066x}", 1))),
+        "eth_getTransactionCount" => Ok(json!(format!("{:#x}", mined))),
+        "eth_getTransactionReceipt" => {
+            let poll = polls.fetch_add(1, Ordering::SeqCst) as usize;
+            if failed_poll == Some(poll) {
+                return Err("node unavailable".to_string());
+            }
+            if receipt_from_poll.is_none_or(|from| poll < from) {
+                return Ok(Value::Null);
+            }
+            Ok(json!({
+                "transactionHash": format!("{:#066x}", 1),
+                "transactionIndex": "0x0",
+                "blockHash": format!("{:#066x}", 2),
+                "blockNumber": "0x10",
+                "from": format!("{:#x}", Address::repeat_byte(1)),
+                "to": format!("{:#x}", Address::repeat_byte(1)),
+                "cumulativeGasUsed": "0x5208",
+                "gasUsed": "0x5208",
+                "effectiveGasPrice": "0x64",
+                "contractAddress": null,
+                "logs": [],
+                "logsBloom": format!("0x{}", "00".repeat(256)),
+                "type": "0x2",
+                "status": "0x1",
+            }))
+        }
+        _ => fee_response(method),
+    })
+    .await;
+    let provider = Arc::new(provider);
+    let tracker = TxTracker::new(provider.clone())
+        .with_poll_interval(Duration::from_millis(10))
+        .with_timeout(Duration::from_millis(100));
+    let collector = tracker.collector();
+    let outcomes = collector.get_event_stream().await.unwrap();
+    MempoolExecutor::new(provider)
+        .with_tracker(tracker)
+        .execute(submit_tx(Some(3)))
+        .await
+        .unwrap();
+    let outcome = outcomes.into_future().await.0.unwrap();
+    outcome
+}
+
+/// Test that submitted transactions are watched until they are included,
+/// replaced or time out.
+#[tokio::test]
+async fn test_mempool_executor_tracks_txs() {
+    let outcome = track_tx(None, Some(2), 3).await;
+    assert_eq!(outcome.hash, TxHash::with_last_byte(1));
+    assert_eq!(outcome.sender, Some(Address::repeat_byte(1)));
+    assert_eq!(outcome.nonce, Some(3));
+    match outcome.status {
+        TxStatus::Included {
+            block_number,
+            gas_used,
+            effective_gas_price,
+            success,
+            ..
+        } => {
+            assert_eq!(block_number, 16);
+            assert_eq!(gas_used, 21000);
+            assert_eq!(effective_gas_price, 100);
+            assert!(success);
+        }
+        status => panic!("expected the transaction to be included, got {status:?}"),
+    }
+
+    assert!(matches!(
+        track_tx(None, None, 4).await.status,
+        TxStatus::Replaced
+    ));
+    assert!(matches!(
+        track_tx(None, None, 3).await.status,
+        TxStatus::TimedOut
+    ));
+}
+
+/// Test that a failed poll does not stop a transaction from being tracked.
+#[tokio::test]
+async fn test_tx_tracker_retries_failed_polls() {
+    let outcome = track_tx(Some(0), Some(1), 3).await;
+    assert!(matches!(outcome.status, TxStatus::Included { .. }));
+}
+
+/// Test that pending transactions can be sped up and cancelled, keeping their
+/// nonce and bumping their fees by at least 12.5%.
+#[tokio::test]
+async fn test_mempool_executor_replaces_txs() {
+    let sent = Arc::new(Mutex::new(vec![]));
+    let recorded = sent.clone();
+    let provider = spawn_mock_node(move |method, params| match method {
+        "eth_estimateGas" => Ok(json!("0x5208")),
+        "eth_sendTransaction" => {
+            recorded.lock().unwrap().push(params[0].clone());
+            Ok(json!(format!("{:#066x}", 1)))
+        }
+        _ => fee_response(method),
+    })
+    .await;
+    let executor = MempoolExecutor::new(Arc::new(provider));
+    let sender = Address::repeat_byte(1);
+
+    let unknown = SpeedUpTx { sender, nonce: 4 };
+    assert!(executor.execute(unknown).await.is_err());
+
+    executor.execute(submit_tx(Some(3))).await.unwrap();
+    executor
+        .execute(SpeedUpTx { sender, nonce: 3 })
+        .await
+        .unwrap();
+    executor
+        .execute(CancelTx { sender, nonce: 3 })
+        .await
+        .unwrap();
+
+    let sent = sent.lock().unwrap().clone();
+    let fees: Vec<_> = sent
+        .iter()
+        .map(|tx| {
+            (
+                quantity(&tx["nonce"]),
+                quantity(&tx["maxFeePerGas"]),
+                quantity(&tx["maxPriorityFeePerGas"]),
+            )
+        })
+        .collect();
+    // The initial fees are 2 * 110 + 2 and 2 wei per gas.
+    assert_eq!(fees, vec![(3, 222, 2), (3, 250, 3), (3, 282, 4)]);
+    let cancel = &sent[2];
+    assert_eq!(cancel["to"], json!(format!("{:#x}", sender)));
+    assert_eq!(cancel["value"], "0x0");
+}
+
+/// A transfer signed by the wallet, ready to be sent in a bundle.
+fn bundle_tx_request(wallet: &EthereumWallet, nonce: u64) -> WithOtherFields<TransactionRequest> {
+    let from = NetworkWallet::<Ethereum>::default_signer_address(wallet);
+    WithOtherFields::new(
+        TransactionRequest::default()
+            .with_from(from)
+            .with_to(Address::repeat_byte(1))
+            .with_nonce(nonce)
+            .with_chain_id(1)
+            .with_gas_limit(21000)
+            .with_max_fee_per_gas(100)
+            .with_max_priority_fee_per_gas(2),
+    )
+}
+
+/// Answers `eth_callBundle` and `eth_sendBundle`, reverting the transactions
+/// with the given hashes in simulations and failing them with an error, but
+/// without a revert, if they are in `failed`, and records the requests.
+async fn spawn_mock_relay(
+    reverted: Vec<TxHash>,
+    failed: Vec<TxHash>,
+) -> (RelayClient, Address, Arc<Mutex<Vec<MockRequest>>>) {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = requests.clone();
+    let url = spawn_mock_server(move |request| {
+        let response = match request.method.as_str() {
+            "eth_callBundle" => {
+                let results: Vec<Value> = request.params[0]["txs"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|tx| {
+                        let tx: Bytes = serde_json::from_value(tx.clone()).unwrap();
+                        let hash = keccak256(&tx);
+                        let mut result = json!({
+                            "coinbaseDiff": "0",
+                            "ethSentToCoinbase": "0",
+                            "fromAddress": Address::ZERO,
+                            "gasFees": "0",
+                            "gasPrice": "0",
+                            "gasUsed": "0x5208",
+                            "toAddress": Address::repeat_byte(1),
+                            "txHash": hash,
+                        });
+                        match reverted.contains(&hash) {
+                            true => result["revert"] = json!("0x"),
+                            false => result["value"] = json!("0x"),
+                        }
+                        if failed.contains(&hash) {
+                            result["error"] = json!("nonce too low");
+                        }
+                        result
+                    })
+                    .collect();
+                Ok(json!({
+                    "bundleHash": TxHash::ZERO,
+                    "bundleGasPrice": "0",
+                    "coinbaseDiff": "0",
+                    "ethSentToCoinbase": "0",
+                    "gasFees": "0",
+                    "results": results,
+                    "stateBlockNumber": "0x63",
+                    "totalGasUsed": "0xa410",
+                }))
+            }
+            "eth_sendBundle" => Ok(json!({"bundleHash": TxHash::repeat_byte(0xbb)})),
+            "eth_sendPrivateTransaction" => {
+                let tx: Bytes = serde_json::from_value(request.params[0]["tx"].clone()).unwrap();
+                Ok(json!(keccak256(&tx)))
+            }
+            method => Err(format!("unexpected method {}", method)),
+        };
+        recorded.lock().unwrap().push(request.clone());
+        response
+    })
+    .await;
+    let auth_signer = PrivateKeySigner::random();
+    let auth_address = auth_signer.address();
+    (RelayClient::new(url, auth_signer), auth_address, requests)
+}
+
+#[tokio::test]
+async fn test_bundle_executor_sends_bundles() {
+    let wallet = EthereumWallet::from(PrivateKeySigner::random());
+    let signed = bundle_tx_request(&wallet, 0)
+        .inner
+        .build(&wallet)
+        .await
+        .unwrap()
+        .encoded_2718();
+    let signed_hash = keccak256(&signed);
+    let bundle = SubmitBundle {
+        txs: vec![
+            BundleTx::Signed(signed.into()),
+            BundleTx::Unsigned(Box::new(bundle_tx_request(&wallet, 1))),
+        ],
+        block_number: 100,
+        ..Default::default()
+    };
+
+    // The simulated bundle is sent, with a signature of the auth key.
+    let (relay, auth_address, requests) = spawn_mock_relay(vec![], vec![]).await;
+    let executor = BundleExecutor::new(relay)
+        .with_wallet(wallet.clone())
+        .with_simulation();
+    executor.execute(bundle.clone()).await.unwrap();
+    let requests = std::mem::take(&mut *requests.lock().unwrap());
+    let methods: Vec<_> = requests.iter().map(|r| r.method.as_str()).collect();
+    assert_eq!(methods, ["eth_callBundle", "eth_sendBundle"]);
+    let sent = &requests[1];
+    assert_eq!(sent.params[0]["txs"].as_array().unwrap().len(), 2);
+    assert_eq!(sent.params[0]["blockNumber"], "0x64");
+    let (address, signature) = sent.signature.as_ref().unwrap().split_once(':').unwrap();
+    let signature: PrimitiveSignature = signature.parse().unwrap();
+    let message = format!("{:?}", keccak256(&sent.body));
+    assert_eq!(address.parse::<Address>().unwrap(), auth_address);
+    assert_eq!(
+        signature.recover_address_from_msg(message).unwrap(),
+        auth_address
+    );
+
+    // A bundle with a reverting transaction is rejected before it is sent...
+    let (relay, _, requests) = spawn_mock_relay(vec![signed_hash], vec![]).await;
+    let executor = BundleExecutor::new(relay)
+        .with_wallet(wallet.clone())
+        .with_simulation();
+    let err = executor.execute(bundle.clone()).await.unwrap_err();
+    assert!(matches!(err, Error::Rejected(Rejection::Reverted(_))));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // ...unless the transaction is allowed to revert.
+    let allowed = SubmitBundle {
+        reverting_tx_hashes: vec![signed_hash],
+        ..bundle.clone()
+    };
+    executor.execute(allowed).await.unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 3);
+
+    // A transaction that fails without reverting also rejects the bundle.
+    let (relay, _, requests) = spawn_mock_relay(vec![], vec![signed_hash]).await;
+    let executor = BundleExecutor::new(relay)
+        .with_wallet(wallet.clone())
+        .with_simulation();
+    let err = executor.execute(bundle).await.unwrap_err();
+    match err {
+        Error::Rejected(Rejection::Reverted(reason)) => assert!(reason.contains("nonce too low")),
+        err => panic!("unexpected error: {err}"),
+    }
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+/// A relay that never answers in time.
+struct StalledRelay;
+
+#[async_trait]
+impl Relay for StalledRelay {
+    fn name(&self) -> &str {
+        "stalled"
+    }
+
+    async fn send_bundle(&self, _bundle: EthSendBundle) -> Result<EthBundleHash> {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        unreachable!()
+    }
+
+    async fn send_private_transaction(&self, _tx: PrivateTransactionRequest) -> Result<TxHash> {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        unreachable!()
+    }
+}
+
+#[tokio::test]
+async fn test_multi_relay_executor_fans_out() {
+    let wallet = EthereumWallet::from(PrivateKeySigner::random());
+    let (relay, _, requests) = spawn_mock_relay(vec![], vec![]).await;
+    let failing = spawn_mock_server(|_| Err("bundle rejected".to_string())).await;
+    let executor = MultiRelayExecutor::new()
+        .with_wallet(wallet.clone())
+        .with_relay(relay.with_name("mock"))
+        .with_relay(RelayClient::new(failing, PrivateKeySigner::random()).with_name("failing"))
+        .with_relay_timeout(StalledRelay, Duration::from_millis(100));
+
+    // Every relay is reported, in order, and slow relays time out.
+    let bundle = SubmitBundle {
+        txs: vec![BundleTx::Unsigned(Box::new(bundle_tx_request(&wallet, 0)))],
+        block_number: 100,
+        ..Default::default()
+    };
+    let results = executor.send_bundle(&bundle).await.unwrap();
+    let relays: Vec<_> = results.iter().map(|r| r.relay.as_str()).collect();
+    assert_eq!(relays, ["mock", "failing", "stalled"]);
+    assert_eq!(
+        results[0].result.as_ref().unwrap().bundle_hash,
+        TxHash::repeat_byte(0xbb)
+    );
+    assert!(results[1].result.is_err());
+    assert!(matches!(results[2].result, Err(Error::Timeout(_))));
+    executor.execute(bundle).await.unwrap();
+
+    let tx = bundle_tx_request(&wallet, 1)
+        .inner
+        .build(&wallet)
+        .await
+        .unwrap();
+    let action = SubmitPrivateTx {
+        tx: BundleTx::Signed(tx.encoded_2718().into()),
+        max_block_number: Some(110),
+        preferences: Default::default(),
+    };
+    let results = executor.send_private_tx(&action).await.unwrap();
+    assert_eq!(*results[0].result.as_ref().unwrap(), *tx.tx_hash());
+    let requests = std::mem::take(&mut *requests.lock().unwrap());
+    let sent = requests.last().unwrap();
+    assert_eq!(sent.method, "eth_sendPrivateTransaction");
+    assert_eq!(sent.params[0]["maxBlockNumber"], "0x6e");
+
+    // The action only fails if no relay accepts it.
+    let executor = MultiRelayExecutor::new()
+        .with_timeout(Duration::from_millis(100))
+        .with_relay(StalledRelay);
+    assert!(executor.execute(action).await.is_err());
+}
+
+/// An executor failing its first `failures` calls with the given error.
+struct FlakyExecutor {
+    failures: u32,
+    error: fn() -> Error,
+    delay: Duration,
+    executed: Arc<Mutex<Vec<(u64, tokio::time::Instant)>>>,
+}
+
+impl FlakyExecutor {
+    fn new(failures: u32, error: fn() -> Error) -> Self {
+        Self {
+            failures,
+            error,
+            delay: Duration::ZERO,
+            executed: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl Executor<u64> for FlakyExecutor {
+    async fn execute(&self, action: u64) -> Result<()> {
+        tokio::time::sleep(self.delay).await;
+        let mut executed = self.executed.lock().unwrap();
+        executed.push((action, tokio::time::Instant::now()));
+        match executed.len() as u32 <= self.failures {
+            true => Err((self.error)()),
+            false => Ok(()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_executor_combinators() {
+    let timeout = || Error::Timeout(Duration::ZERO);
+    let rejected = || Error::Rejected(Rejection::Underpriced);
+
+    // Retryable errors are retried until the action succeeds...
+    let flaky = FlakyExecutor::new(2, timeout);
+    let executed = flaky.executed.clone();
+    let retry = RetryExecutor::new(Box::new(flaky))
+        .with_backoff(Duration::from_millis(10), Duration::from_millis(15));
+    retry.execute(1).await.unwrap();
+    let times: Vec<_> = executed.lock().unwrap().iter().map(|(_, at)| *at).collect();
+    assert_eq!(times.len(), 3);
+    assert!(times[1] - times[0] >= Duration::from_millis(10));
+    assert!(times[2] - times[1] >= Duration::from_millis(15));
+
+    // ...or the retries run out, and other errors are not retried.
+    let retry = RetryExecutor::new(Box::new(FlakyExecutor::new(5, timeout)))
+        .with_max_retries(2)
+        .with_backoff(Duration::ZERO, Duration::ZERO);
+    assert!(matches!(retry.execute(1).await, Err(Error::Timeout(_))));
+    let flaky = FlakyExecutor::new(1, rejected);
+    let executed = flaky.executed.clone();
+    assert!(RetryExecutor::new(Box::new(flaky))
+        .execute(1)
+        .await
+        .is_err());
+    assert_eq!(executed.lock().unwrap().len(), 1);
+
+    // Slow actions time out.
+    let mut slow = FlakyExecutor::new(0, timeout);
+    slow.delay = Duration::from_secs(60);
+    let limit = Duration::from_millis(10);
+    let err = TimeoutExecutor::new(Box::new(slow), limit)
+        .execute(1)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Timeout(d) if d == limit));
+
+    // A burst of actions runs at once, then one action runs per interval.
+    let flaky = FlakyExecutor::new(0, timeout);
+    let executed = flaky.executed.clone();
+    let limited = RateLimitedExecutor::new(Box::new(flaky), 2, Duration::from_millis(50));
+    let start = tokio::time::Instant::now();
+    for action in 0..4 {
+        limited.execute(action).await.unwrap();
+    }
+    let delays: Vec<_> = executed
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, at)| *at - start)
+        .collect();
+    assert!(delays[1] < Duration::from_millis(50));
+    assert!(delays[2] >= Duration::from_millis(50));
+    assert!(delays[3] >= Duration::from_millis(100));
+
+    // Duplicates are dropped within the window, unless the first one failed.
+    let flaky = FlakyExecutor::new(1, rejected);
+    let executed = flaky.executed.clone();
+    let dedup = DedupExecutor::new(
+        Box::new(flaky),
+        Duration::from_millis(50),
+        |action: &u64| action % 10,
+    );
+    for action in [1, 11, 21, 2, 12] {
+        let _ = dedup.execute(action).await;
+    }
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    dedup.execute(31).await.unwrap();
+    let actions: Vec<_> = executed.lock().unwrap().iter().map(|(a, _)| *a).collect();
+    assert_eq!(actions, [1, 11, 2, 31]);
+}
+
+/// A collector that emits each event after waiting for its delay, in
+/// milliseconds, then ends.
+struct TimedCollector {
+    events: Vec<(u64, u64)>,
+}
+
+#[async_trait]
+impl Collector<u64> for TimedCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        let stream = futures::stream::iter(self.events.clone()).then(|(event, delay)| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            event
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// Collects all the events of a collector whose stream ends.
+async fn collect_events<E>(collector: impl Collector<E>) -> Vec<E> {
+    collector.get_event_stream().await.unwrap().collect().await
+}
+
+fn timed(events: &[(u64, u64)]) -> Box<dyn Collector<u64>> {
+    Box::new(TimedCollector {
+        events: events.to_vec(),
+    })
+}
+
+#[tokio::test]
+async fn test_collector_combinators() {
+    let events = [(1, 0), (2, 0), (3, 0), (4, 0)];
+    let even = FilterCollector::new(timed(&events), |event: &u64| event.is_multiple_of(2));
+    assert_eq!(collect_events(even).await, [2, 4]);
+    let halves = FilterMapCollector::new(timed(&events), |event: u64| {
+        event.is_multiple_of(2).then_some(event / 2)
+    });
+    assert_eq!(collect_events(halves).await, [1, 2]);
+
+    // Two sources reporting the same events are merged and deduplicated.
+    let merged = MergeCollector::new(vec![
+        timed(&[(1, 0), (2, 20), (3, 20)]),
+        timed(&[(2, 10), (1, 0), (3, 20), (4, 0)]),
+    ]);
+    let mut unique = collect_events(DedupCollector::new(Box::new(merged), 16, |e: &u64| *e)).await;
+    unique.sort();
+    assert_eq!(unique, [1, 2, 3, 4]);
+    let forgetful = DedupCollector::new(
+        timed(&[(1, 0), (2, 0), (1, 0), (3, 0), (1, 0)]),
+        1,
+        |e: &u64| *e,
+    );
+    assert_eq!(collect_events(forgetful).await, [1, 2, 1, 3, 1]);
+
+    // Throttling keeps the first event of each interval, debouncing the last
+    // event of each burst.
+    let bursts = [(1, 0), (2, 10), (3, 10), (4, 100), (5, 10)];
+    let throttled = ThrottleCollector::new(timed(&bursts), Duration::from_millis(50));
+    assert_eq!(collect_events(throttled).await, [1, 4]);
+    let debounced = DebounceCollector::new(timed(&bursts), Duration::from_millis(50));
+    assert_eq!(collect_events(debounced).await, [3, 5]);
+
+    // Batches are emitted when full or after the wait.
+    let batched = BatchCollector::new(timed(&bursts), 2, Duration::from_millis(50));
+    assert_eq!(
+        collect_events(batched).await,
+        [vec![1, 2], vec![3], vec![4, 5]]
+    );
+}
+
+#[tokio::test]
+async fn test_strategy_combinators() {
+    // A strategy over numbers plugged into a pipeline of strings.
+    let mut mapped = StrategyMap::new(
+        Box::new(EchoStrategy),
+        |event: &str| event.parse::<u64>().ok(),
+        |action: u64| action.to_string(),
+    );
+    mapped.sync_state().await.unwrap();
+    assert_eq!(mapped.process_event("7").await, ["7"]);
+    assert!(mapped.process_event("seven").await.is_empty());
+
+    // The actions of a strategy pass through a risk filter.
+    let mut calls = 0;
+    let sizing = FnStrategy::new(move |event: u64| {
+        calls += 1;
+        vec![event * 10, event * 100, calls]
+    });
+    let risk_filter = FilteredStrategy::new(Box::new(EchoStrategy), |size: &u64| *size <= 500);
+    let mut chained = ChainedStrategy::new(Box::new(sizing), Box::new(risk_filter));
+    chained.sync_state().await.unwrap();
+    assert_eq!(chained.process_event(2).await, [20, 200, 1]);
+    assert_eq!(chained.process_event(7).await, [70, 2]);
+}
+
+/// An executor recording the nonce of every cancelled transaction.
+#[derive(Default)]
+struct CancelRecorder {
+    nonces: Arc<Mutex<Vec<u64>>>,
+}
+
+#[async_trait]
+impl Executor<CancelTx> for CancelRecorder {
+    async fn execute(&self, action: CancelTx) -> Result<()> {
+        self.nonces.lock().unwrap().push(action.nonce);
+        Ok(())
+    }
+}
+
+/// Test that an engine can run over [Events] and [Actions], with components
+/// written for a single event or action type.
+#[tokio::test]
+async fn test_engine_runs_over_events_and_actions() {
+    let blocks = CollectorMap::new(Box::new(VecCollector { events: vec![1, 2] }), |number| {
+        NewBlock {
+            hash: BlockHash::ZERO,
+            number,
+        }
+    });
+    let cancels = CancelRecorder::default();
+    let cancelled = cancels.nonces.clone();
+
+    let mut engine: Engine<Events, Actions> = Engine::new();
+    engine.add_collector(Box::new(Events::collector(Box::new(blocks))));
+    engine.add_strategy(Box::new(FnStrategy::new(|event: Events| match event {
+        Events::NewBlock(block) => vec![CancelTx {
+            sender: Address::ZERO,
+            nonce: block.number,
+        }
+        .into()],
+        _ => vec![],
+    })));
+    engine.add_executor(Box::new(Actions::executor(Box::new(cancels))));
+    let shutdown = engine.shutdown_handle();
+    let _set = engine.run().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    shutdown.shutdown().await;
+
+    let mut cancelled = cancelled.lock().unwrap().clone();
+    cancelled.sort();
+    assert_eq!(cancelled, [1, 2]);
+    assert!(SpeedUpTx::try_from(Actions::from(CancelTx {
+        sender: Address::ZERO,
+        nonce: 0,
+    }))
+    .is_err());
+}
+
+/// A JSON-RPC block header with the given number and a base fee of 7 wei.
+fn header_json(number: u64) -> Value {
+    json!({
+        "hash": BlockHash::with_last_byte(number as u8),
+        "parentHash": BlockHash::with_last_byte(number as u8 - 1),
+        "sha3Uncles": BlockHash::ZERO,
+        "miner": Address::ZERO,
+        "stateRoot": BlockHash::ZERO,
+        "transactionsRoot": BlockHash::ZERO,
+        "receiptsRoot": BlockHash::ZERO,
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "difficulty": "0x0",
+        "number": format!("{:#x}", number),
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x5208",
+        "timestamp": "0x64",
+        "extraData": "0x",
+        "mixHash": BlockHash::ZERO,
+        "nonce": "0x0000000000000000",
+        "baseFeePerGas": "0x7",
+    })
+}
+
+#[tokio::test]
+async fn test_block_details_collector_fetches_details() {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = requests.clone();
+    let provider = spawn_mock_node(move |method, params| {
+        recorded.lock().unwrap().push(method.to_string());
+        match method {
+            "eth_getBlockByHash" => {
+                assert_eq!(params[1], true);
+                let mut block = header_json(2);
+                block["transactions"] = json!([]);
+                block["uncles"] = json!([]);
+                Ok(block)
+            }
+            "eth_getBlockReceipts" => Ok(json!([])),
+            _ => Err(format!("unexpected method {method}")),
+        }
+    })
+    .await;
+    let provider = Arc::new(provider);
+    let header: AnyRpcHeader = serde_json::from_value(header_json(2)).unwrap();
+
+    // The header alone needs no request.
+    let details = BlockDetailsCollector::new(provider.clone())
+        .fetch_details(header.clone())
+        .await
+        .unwrap();
+    assert_eq!(details.header.inner.base_fee_per_gas, Some(7));
+    assert_eq!(details.header.inner.timestamp, 100);
+    assert_eq!(details.new_block().number, 2);
+    assert!(details.block.is_none() && details.receipts.is_none());
+    assert!(requests.lock().unwrap().is_empty());
+
+    let details = BlockDetailsCollector::new(provider)
+        .with_transactions()
+        .with_receipts()
+        .fetch_details(header)
+        .await
+        .unwrap();
+    assert_eq!(details.block.unwrap().header.hash, details.header.hash);
+    assert_eq!(details.receipts.unwrap().len(), 0);
+    let mut requests = requests.lock().unwrap().clone();
+    requests.sort();
+    assert_eq!(requests, ["eth_getBlockByHash", "eth_getBlockReceipts"]);
+}
+
+/// Test that a block whose receipts cannot be fetched is still emitted, with
+/// only its header.
+#[tokio::test]
+async fn test_block_details_collector_emits_header_on_failure() {
+    let provider = spawn_mock_node(|method, params| match method {
+        "eth_getBlockReceipts" if params[0]["blockHash"] == header_json(3)["hash"] => {
+            Err("receipts unavailable".to_string())
+        }
+        "eth_getBlockReceipts" => Ok(json!([])),
+        _ => Err(format!("unexpected method {method}")),
+    })
+    .await;
+    let collector = BlockDetailsCollector::new(Arc::new(provider)).with_receipts();
+    let headers = (2..5).map(|number| serde_json::from_value(header_json(number)).unwrap());
+
+    let details: Vec<_> = collector
+        .details_stream(futures::stream::iter(headers))
+        .collect()
+        .await;
+
+    let numbers: Vec<_> = details.iter().map(|d| d.new_block().number).collect();
+    assert_eq!(numbers, [2, 3, 4]);
+    assert!(details[0].receipts.is_some());
+    assert!(details[1].receipts.is_none() && details[1].block.is_none());
+    assert!(details[2].receipts.is_some());
+}
+
+/// A block on a fork, whose hash is derived from its number and fork.
+fn fork_block(number: u64, fork: u8) -> (NewBlock, BlockHash) {
+    let hash = |number: u64| match number {
+        // Blocks before 3 are shared by all forks.
+        0..3 => BlockHash::with_last_byte(number as u8),
+        _ => BlockHash::left_padding_from(&[fork, number as u8]),
+    };
+    (
+        NewBlock {
+            hash: hash(number),
+            number,
+        },
+        hash(number - 1),
+    )
+}
+
+fn numbers(blocks: &[NewBlock]) -> Vec<u64> {
+    blocks.iter().map(|block| block.number).collect()
+}
+
+#[test]
+fn test_reorg_tracker_detects_reorgs() {
+    let mut tracker = ReorgTracker::new(4);
+    let mut push = |number, fork| {
+        let (block, parent) = fork_block(number, fork);
+        tracker.push(block, parent)
+    };
+    for number in 1..=5 {
+        assert!(matches!(push(number, 0), Some(BlockEvent::NewBlock(_))));
+    }
+    assert!(push(5, 0).is_none());
+
+    // The new chain forks after block 2, dropping blocks 3 to 5.
+    let Some(BlockEvent::Reorg { dropped, new_head }) = push(3, 1) else {
+        panic!("expected a reorg");
+    };
+    assert_eq!(numbers(&dropped), [3, 4, 5]);
+    assert_eq!(dropped[0].hash, fork_block(3, 0).0.hash);
+    assert_eq!(new_head.hash, fork_block(3, 1).0.hash);
+    assert!(matches!(push(4, 1), Some(BlockEvent::NewBlock(_))));
+
+    // A block whose parent was replaced drops it too.
+    let Some(BlockEvent::Reorg { dropped, .. }) = push(5, 2) else {
+        panic!("expected a reorg");
+    };
+    assert_eq!(numbers(&dropped), [4]);
+
+    // Every remembered block at or above the new block is dropped.
+    let Some(BlockEvent::Reorg { dropped, .. }) = push(3, 3) else {
+        panic!("expected a reorg");
+    };
+    assert_eq!(numbers(&dropped), [3, 5]);
+}